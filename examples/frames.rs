@@ -36,7 +36,7 @@ fn delta(prev: &[u8], next: &[u8]) -> Vec<Vec<u8>> {
     for y in 0..128 {
         let mut state: u8 = 2;
         let mut start: u8 = 0;
-        let mut end: u8 = 0;
+        let mut end: u8;
         let mut prog: bool = false;
         for x in 0..178 {
             if next[y * 178 + x] == state { continue; }
@@ -0,0 +1,378 @@
+//! LMS2012 assembler.
+//!
+//! Assemble LEGO's LMS assembler syntax into bytecode objects, using [`crate::encode`] rules.
+//! Output [`Program`] can be wrapped into executable image.
+//!
+//! ### Syntax
+//!  - `define NAME value` constant
+//!  - `DATA8 name`, `DATA16`, `DATA32`, `DATAF`, `HANDLE` (DATA16) & `DATAS name size` variable.
+//!    Global outside objects, local inside objects
+//!  - `vmthread NAME { ... }` & `subcall NAME { ... }` objects. Object name is its id as constant
+//!  - `IN_8`, `IN_16`, `IN_32`, `IN_F`, `IN_S size`, `OUT_*` & `IO_*` subcall parameters
+//!  - `OPCODE(param, ...)` instruction without `op` prefix, sub-command as first parameter name
+//!  - `name:` label, used as parameter of jumping OpCode
+//!  - Constants: `42`, `-1`, `0x1B`, `1.5F`, `'text'`, handle: `@name`
+//!  - Comments: `//` and `/* */`
+//!
+//! Parameter count of OpCodes with variable parameters (e.g. `CALL`) is inserted automatically.
+//! Objects end with `OBJECT_END` (`RETURN`, `OBJECT_END` for subcall). \
+//! Errors report line number as position.
+//! # Example
+//! ```
+//! use ev3_dc::asm::assemble;
+//!
+//! let program = assemble("
+//!     vmthread MAIN
+//!     {
+//!         DATA8 Count // Loop counter
+//!         MOVE8_8(0, Count)
+//!     Loop:
+//!         ADD8(Count, 1, Count)
+//!         JR_LT8(Count, 10, Loop)
+//!         UI_WRITE(LED, 4)
+//!     }
+//! ").unwrap();
+//! assert_eq!(program.objects[0].name, "MAIN");
+//! assert_eq!(program.objects[0].bytecode, vec![
+//!     0x30, 0x00, 0x40, // MOVE8_8
+//!     0x10, 0x40, 0x01, 0x40, // ADD8
+//!     0x64, 0x40, 0x0A, 0x82, 0xF6, 0xFF, // JR_LT8 -10
+//!     0x82, 0x1B, 0x04, // UI_WRITE LED
+//!     0x0A // OBJECT_END
+//! ]);
+//!
+//! let program = assemble("
+//!     DATA32 Result
+//!     vmthread MAIN {
+//!         CALL(Double, 21, Result)
+//!     }
+//!     subcall Double {
+//!         IN_8 Value
+//!         OUT_32 Twice
+//!         DATA32 Tmp
+//!         MOVE8_32(Value, Tmp)
+//!         ADD32(Tmp, Tmp, Twice)
+//!     }
+//! ").unwrap();
+//! assert_eq!(program.globals, 4);
+//! assert_eq!(program.objects[0].bytecode, vec![0x09, 0x02, 0x02, 0x15, 0x60, 0x0A]);
+//! assert_eq!(&program.objects[1].bytecode[..3], &[0x02, 0x80, 0x42]);
+//! assert_eq!(program.objects[1].locals, 12);
+//!
+//! // Names ending with `F` aren't floats
+//! let program = assemble("
+//!     define LED_OFF 0
+//!     vmthread MAIN {
+//!         DATA8 BUF
+//!         UI_WRITE(LED, LED_OFF)
+//!         MOVE8_8(2.5F, BUF)
+//!     }
+//! ").unwrap();
+//! assert_eq!(program.objects[0].bytecode, vec![0x82, 0x1B, 0x00, 0x30, 0x83, 0x00, 0x00, 0x20, 0x40, 0x40, 0x0A]);
+//!
+//! // Recursive defines are rejected
+//! assert!(assemble("define A A\nvmthread MAIN {\n UI_WRITE(LED, A)\n}\n").is_err());
+//! assert!(assemble("define A B\ndefine B A\nvmthread MAIN {\n UI_WRITE(LED, A)\n}\n").is_err());
+//! ```
+
+use alloc::{ collections::BTreeMap, format, string::{ String, ToString }, vec, vec::Vec };
+use crate::{ encode, DataType, Encoding::*, MemScope, ValError, Variable };
+use crate::opcode::{ by_name, Param, Params };
+use crate::utils::{ auto_const, ChainByte };
+
+/// Object type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectKind {
+    /// Independent thread. First object is started with program
+    VMThread,
+    /// Subroutine called with `CALL`
+    SubCall,
+}
+
+/// Assembled object
+#[derive(Debug, Clone, PartialEq)]
+pub struct Object {
+    /// Object type
+    pub kind: ObjectKind,
+    /// Object name
+    pub name: String,
+    /// Local memory size in bytes
+    pub locals: u32,
+    /// Object bytecode, subcall starts with parameter description
+    pub bytecode: Vec<u8>,
+}
+
+/// Assembled program
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    /// Global memory size in bytes
+    pub globals: u32,
+    /// Objects in declaration order. Object id is index + 1
+    pub objects: Vec<Object>,
+}
+
+// Subcall parameter flags
+const CALLPAR_IN: u8 = 0x80;
+const CALLPAR_OUT: u8 = 0x40;
+
+#[derive(Default)]
+struct Memory {
+    vars: BTreeMap<String, Variable>,
+    size: usize,
+}
+
+impl Memory {
+    fn declare(&mut self, name: &str, dtype: DataType, scope: MemScope, line: usize) -> Result<(), ValError> {
+        if self.vars.contains_key(name) { return Err(ValError::Parse(line, format!("Duplicate variable {name}"))) }
+        let offset = self.size.next_multiple_of(dtype.align());
+        self.size = offset + dtype.size();
+        let offset = u16::try_from(offset).map_err(|_| ValError::Parse(line, "Memory overflow".to_string()))?;
+        self.vars.insert(name.to_string(), Variable { scope, offset, dtype });
+        Ok(())
+    }
+}
+
+struct Assembler {
+    defines: BTreeMap<String, String>,
+    objects: BTreeMap<String, usize>,
+    globals: Memory,
+}
+
+/// Assemble LMS source into [`Program`]
+pub fn assemble(src: &str) -> Result<Program, ValError> {
+    let lines: Vec<(usize, String)> = strip_comments(src).lines().enumerate()
+        .map(|(i, l)| (i + 1, l.trim().to_string()))
+        .filter(|(_, l)| !l.is_empty()).collect();
+    let mut asm = Assembler { defines: BTreeMap::new(), objects: BTreeMap::new(), globals: Memory::default() };
+    // First pass: object ids
+    for (_, line) in &lines {
+        if let Some((ObjectKind::VMThread | ObjectKind::SubCall, name)) = object_header(line) {
+            let id = asm.objects.len() + 1;
+            asm.objects.insert(name.to_string(), id);
+        }
+    }
+    let mut objects = vec![];
+    let mut iter = lines.iter();
+    while let Some((num, line)) = iter.next() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if let Some((kind, name)) = object_header(line) {
+            if !line.ends_with('{') && iter.next().is_none_or(|(_, open)| open != "{") {
+                return Err(ValError::Parse(*num, "Expect '{'".to_string()));
+            }
+            let mut body = vec![];
+            loop {
+                match iter.next() {
+                    Some((_, l)) if l == "}" => break,
+                    Some(l) => body.push(l),
+                    None => return Err(ValError::Parse(*num, format!("Unclosed object {name}")))
+                }
+            }
+            objects.push(asm.object(kind, name, &body)?);
+        } else if words[0] == "define" && words.len() == 3 {
+            asm.defines.insert(words[1].to_string(), words[2].to_string());
+        } else if let Some((dtype, name)) = declaration(&words, *num)? {
+            asm.globals.declare(name, dtype, MemScope::Global, *num)?;
+        } else {
+            return Err(ValError::Parse(*num, format!("Unexpected {line:?}")));
+        }
+    }
+    Ok(Program { globals: asm.globals.size as u32, objects })
+}
+
+// Remove `//` & `/* */` comments, keeping line count & string content
+fn strip_comments(src: &str) -> String {
+    let mut out = String::new();
+    let mut chars = src.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        if c == '\'' { quoted = !quoted; }
+        if !quoted && c == '/' && chars.peek() == Some(&'/') {
+            while chars.peek().is_some_and(|c| *c != '\n') { chars.next(); }
+        } else if !quoted && c == '/' && chars.peek() == Some(&'*') {
+            chars.next();
+            while let Some(c) = chars.next() {
+                if c == '\n' { out.push('\n'); }
+                if c == '*' && chars.peek() == Some(&'/') { chars.next(); break; }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn object_header(line: &str) -> Option<(ObjectKind, &str)> {
+    let mut words = line.trim_end_matches('{').split_whitespace();
+    let kind = match words.next()? {
+        "vmthread" => ObjectKind::VMThread,
+        "subcall" => ObjectKind::SubCall,
+        _ => return None
+    };
+    Some((kind, words.next()?))
+}
+
+// Parse variable declaration
+fn declaration<'a>(words: &[&'a str], line: usize) -> Result<Option<(DataType, &'a str)>, ValError> {
+    let size = |i: usize| -> Result<usize, ValError> {
+        words.get(i).and_then(|w| parse_int(w)).and_then(|v| usize::try_from(v).ok())
+            .ok_or(ValError::Parse(line, "Expect string size".to_string()))
+    };
+    let kind = match words[0].split_once('_') {
+        Some(("IN" | "OUT" | "IO", kind)) => kind,
+        _ => words[0]
+    };
+    let dtype = match kind {
+        "DATA8" | "8" => DataType::DATA8,
+        "DATA16" | "16" | "HANDLE" => DataType::DATA16,
+        "DATA32" | "32" => DataType::DATA32,
+        "DATAF" | "F" => DataType::DATAF,
+        "DATAS" | "S" => DataType::DATAS(size(2)?.saturating_sub(1)),
+        _ => return Ok(None)
+    };
+    let name = words.get(1).ok_or(ValError::Parse(line, "Expect variable name".to_string()))?;
+    Ok(Some((dtype, name)))
+}
+
+pub(crate) fn parse_int(word: &str) -> Option<i32> {
+    let (neg, digits) = match word.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, word)
+    };
+    let val = match digits.strip_prefix("0x").or(digits.strip_prefix("0X")) {
+        Some(hex) => i64::from_str_radix(hex, 16).ok()?,
+        None => digits.parse::<i64>().ok()?
+    };
+    i32::try_from(if neg { -val } else { val }).ok()
+}
+
+// Split parameters on commas outside quotes
+fn split_params(text: &str) -> Vec<String> {
+    let mut params = vec![];
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in text.chars() {
+        match c {
+            '\'' => { quoted = !quoted; current.push(c); }
+            ',' if !quoted => { params.push(current.trim().to_string()); current.clear(); }
+            _ => current.push(c)
+        }
+    }
+    if !current.trim().is_empty() || !params.is_empty() { params.push(current.trim().to_string()); }
+    params
+}
+
+impl Assembler {
+    fn object(&self, kind: ObjectKind, name: &str, body: &[&(usize, String)]) -> Result<Object, ValError> {
+        let mut locals = Memory::default();
+        let mut code = ChainByte::new();
+        let mut header = vec![];
+        for (num, line) in body {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if let Some(label) = line.strip_suffix(':') {
+                code.label(label.trim());
+                continue;
+            }
+            let direction = match words[0].split_once('_').map(|(d, _)| d) {
+                Some("IN") => Some(CALLPAR_IN),
+                Some("OUT") => Some(CALLPAR_OUT),
+                Some("IO") => Some(CALLPAR_IN | CALLPAR_OUT),
+                _ => None
+            };
+            if let Some((dtype, var)) = declaration(&words, *num)? {
+                if let Some(flag) = direction {
+                    if kind != ObjectKind::SubCall { return Err(ValError::Parse(*num, "Parameter outside subcall".to_string())) }
+                    header.push(flag + match dtype {
+                        DataType::DATA8 => 0,
+                        DataType::DATA16 => 1,
+                        DataType::DATA32 => 2,
+                        DataType::DATAF => 3,
+                        _ => 4
+                    });
+                }
+                locals.declare(var, dtype, MemScope::Local, *num)?;
+                continue;
+            }
+            self.instruction(line, *num, &locals, &mut code)?;
+        }
+        if kind == ObjectKind::SubCall { code.push(0x08); }
+        code.push(0x0A).resolve()?;
+        let mut bytecode = vec![];
+        if kind == ObjectKind::SubCall {
+            bytecode.push(header.len() as u8);
+            bytecode.extend(header);
+        }
+        bytecode.extend(&code.bytes);
+        Ok(Object { kind, name: name.to_string(), locals: locals.size as u32, bytecode })
+    }
+    fn instruction(&self, line: &str, num: usize, locals: &Memory, code: &mut ChainByte) -> Result<(), ValError> {
+        let (mnemonic, args) = match line.split_once('(') {
+            Some((m, rest)) => (m.trim(), split_params(rest.trim_end().strip_suffix(')')
+                .ok_or(ValError::Parse(num, "Expect ')'".to_string()))?)),
+            None => (line, vec![])
+        };
+        let op = by_name(mnemonic).ok_or(ValError::Parse(num, format!("Unknown OpCode {mnemonic}")))?;
+        let mut args = args.iter();
+        let mut bytes = vec![];
+        let layout = match op.params {
+            Params::Fixed(layout) => layout,
+            Params::Sub(_) => {
+                let arg = args.next().ok_or(ValError::Parse(num, "Expect sub-command".to_string()))?;
+                let sub = op.sub_by_name(arg).or(parse_int(arg).and_then(|c| op.sub(c as u8)))
+                    .ok_or(ValError::Parse(num, format!("Unknown sub-command {arg}")))?;
+                bytes.extend(auto_const(sub.code as i32)?);
+                sub.params
+            }
+        };
+        for kind in layout {
+            match kind {
+                Param::Value => {
+                    let arg = args.next().ok_or(ValError::Parse(num, format!("Missing parameter of {}", op.name)))?;
+                    bytes.extend(self.param(arg, num, locals, &[])?);
+                }
+                Param::Label => {
+                    let arg = args.next().ok_or(ValError::Parse(num, "Expect label".to_string()))?;
+                    code.jump(op.code, bytes, arg);
+                    return Ok(());
+                }
+                Param::Count => {
+                    let rest: Vec<&String> = args.by_ref().collect();
+                    bytes.extend(auto_const(rest.len() as i32)?);
+                    for arg in rest { bytes.extend(self.param(arg, num, locals, &[])?); }
+                }
+            }
+        }
+        if args.next().is_some() { return Err(ValError::Parse(num, format!("Too many parameters for {}", op.name))) }
+        code.push(op.code).add(bytes);
+        Ok(())
+    }
+    // `expanding` holds define names being resolved, to catch recursion
+    fn param(&self, arg: &str, num: usize, locals: &Memory, expanding: &[&str]) -> Result<Vec<u8>, ValError> {
+        if let Some(text) = arg.strip_prefix('\'').and_then(|a| a.strip_suffix('\'')) {
+            return encode(LCS(text));
+        }
+        if let Some(name) = arg.strip_prefix('@') {
+            let var = locals.vars.get(name).or(self.globals.vars.get(name))
+                .ok_or(ValError::Undefined(name.to_string()))?;
+            return match (var.scope, var.offset) {
+                (MemScope::Local, 0..256) => encode(HLV1(var.offset as u8)),
+                (MemScope::Local, _) => encode(HLV2(var.offset)),
+                (MemScope::Global, 0..256) => encode(HGV1(var.offset as u8)),
+                (MemScope::Global, _) => encode(HGV2(var.offset))
+            };
+        }
+        if let Some(var) = locals.vars.get(arg).or(self.globals.vars.get(arg)) { return var.encode() }
+        if let Some(id) = self.objects.get(arg) { return auto_const(*id as i32) }
+        if let Some(val) = self.defines.get(arg) {
+            if expanding.contains(&arg) { return Err(ValError::Parse(num, format!("Recursive define {arg}"))) }
+            return self.param(val, num, locals, &[expanding, &[arg]].concat())
+        }
+        if let Some(val) = parse_int(arg) { return auto_const(val) }
+        let float = arg.strip_suffix('F').unwrap_or(arg);
+        let numeric = arg.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '.');
+        if numeric && (float.contains('.') || float.len() < arg.len()) {
+            let val = float.parse::<f32>().map_err(|_| ValError::Parse(num, format!("Invalid number {arg}")))?;
+            return encode(LCF(val));
+        }
+        Err(ValError::Undefined(arg.to_string()))
+    }
+}
@@ -0,0 +1,423 @@
+//! `ev3dc` command-line tool. Build with `cli` feature
+
+use std::env;
+use std::error::Error as StdError;
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::Duration;
+use ev3_dc::{ encode, Command, DataType, Encoding::*, Error };
+use ev3_dc::funcs::{ brick_info, motor_speed, start_program, stop_motor, stop_program, LedPattern, Sequence, Slot };
+use ev3_dc::parser::Reply;
+use ev3_dc::repl::{ complete, Shell };
+use ev3_dc::system::delete_file;
+use ev3_dc::transport::{ Client, HidTransport, MockBrick, StreamTransport, TcpTransport, Transport };
+use ev3_dc::utils::{ device_id, json_string, package_bytes, port_read, printer, run_length, ChainByte };
+use rustyline::{ Context, Editor, Helper };
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+
+const USAGE: &str = "Usage: ev3dc [--usb [SERIAL] | --bt DEVICE | --ip ADDRESS --serial SERIAL | --mock] [--json] <COMMAND>
+
+Commands:
+  info                        Brick name, versions, memory & battery
+  ports                       Connected devices
+  ls [PATH]                   List folder (default ../prjs/)
+  upload REMOTE [LOCAL]       Copy file from brick
+  download LOCAL REMOTE       Copy file to brick
+  rm PATH                     Delete file or empty folder
+  run PATH                    Run program in user slot
+  stop                        Stop program in user slot
+  beep [HZ] [MS] [VOLUME]     Play tone (default 1000 Hz, 200 ms, 50%)
+  led PATTERN                 off, green, red, orange, *-flash, *-pulse or 0 - 9
+  motor PORTS SPEED [MS]      Run motors (e.g. AB 50), stop after MS
+  motor PORTS stop            Stop motors
+  sensor PORT [MODE]          Read sensor 1 - 4 in SI unit
+  screen IMAGE.pbm            Show 178x128 PBM image
+  raw HEX [GLOBAL]            Send bytecode, reserving GLOBAL reply bytes
+  repl                        Interactive shell, type `help` inside";
+
+const REPL_HELP: &str = "Instructions are separated by `;`, parameters by spaces or commas. Tab completes names
+  ui_write led 4                 OpCode, sub-command & constants
+  output_speed 0 A 50            Motor ports as bit mask
+  ui_draw text 1 0 0 'hello'     Strings
+  input_readsi 0 0 0 -1 ?F       Reply variables: ?8 ?16 ?32 ?F ?S<length> ?N<length>
+  exit                           Leave shell";
+
+type Result<T> = std::result::Result<T, Box<dyn StdError>>;
+
+/// Brick selection
+enum Target {
+    Usb(Option<String>),
+    Bluetooth(String),
+    Wifi(String, String),
+    Mock,
+}
+
+/// Output value, printed as text or JSON
+enum Value {
+    Text(String),
+    Number(f64),
+    List(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    fn json(&self) -> String {
+        match self {
+            Value::Text(text) => json_string(text),
+            Value::Number(num) if num.is_finite() => num.to_string(),
+            Value::Number(_) => "null".to_string(),
+            Value::List(items) => format!("[{}]", items.iter().map(|v| v.json()).collect::<Vec<_>>().join(",")),
+            Value::Object(fields) => format!("{{{}}}", fields.iter()
+                .map(|(k, v)| format!("{}:{}", json_string(k), v.json())).collect::<Vec<_>>().join(","))
+        }
+    }
+    fn text(&self) -> String {
+        match self {
+            Value::Text(text) => text.clone(),
+            Value::Number(num) => num.to_string(),
+            Value::List(items) => items.iter().map(|v| v.text()).collect::<Vec<_>>().join("\n"),
+            Value::Object(fields) => fields.iter()
+                .map(|(k, v)| format!("{k}: {}", v.text())).collect::<Vec<_>>().join("\n")
+        }
+    }
+}
+
+fn text(val: impl ToString) -> Value {
+    Value::Text(val.to_string())
+}
+
+fn fields(fields: Vec<(&str, Value)>) -> Value {
+    Value::Object(fields.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+}
+
+fn connect(target: &Target) -> Result<Client<Box<dyn Transport>>> {
+    let transport: Box<dyn Transport> = match target {
+        Target::Usb(serial) => Box::new(HidTransport::open(serial.as_deref())?),
+        Target::Bluetooth(path) => Box::new(StreamTransport::open(path)?),
+        Target::Wifi(_, serial) if serial.is_empty() => return Err("Missing --serial for WiFi".into()),
+        Target::Wifi(addr, serial) => {
+            let addr = if addr.contains(':') { addr.clone() } else { format!("{addr}:5555") };
+            Box::new(TcpTransport::connect(addr, serial)?)
+        }
+        Target::Mock => Box::new(MockBrick::new()),
+    };
+    Ok(Client::new(transport).0)
+}
+
+// Send bytecode and return reply
+fn direct(client: &mut Client<Box<dyn Transport>>, cmd: &mut Command, bytecode: Vec<u8>) -> std::result::Result<Reply, Error> {
+    cmd.bytecode = bytecode;
+    client.direct(cmd)
+}
+
+fn arg<'a>(args: &'a [String], i: usize, name: &str) -> Result<&'a str> {
+    args.get(i).map(|a| a.as_str()).ok_or(format!("Missing {name}").into())
+}
+
+fn number<T: std::str::FromStr>(args: &[String], i: usize, name: &str, default: Option<T>) -> Result<T> {
+    match (args.get(i), default) {
+        (Some(val), _) => val.parse().map_err(|_| format!("Invalid {name}: {val}").into()),
+        (None, Some(default)) => Ok(default),
+        (None, None) => Err(format!("Missing {name}").into())
+    }
+}
+
+// Motor ports as bit mask, e.g. "AB" = 3
+fn motor_ports(ports: &str) -> Result<u8> {
+    let mut mask = 0;
+    for port in ports.to_ascii_uppercase().chars() {
+        mask |= match port {
+            'A' => 1, 'B' => 2, 'C' => 4, 'D' => 8,
+            _ => return Err(format!("Invalid motor port: {port}").into())
+        };
+    }
+    if mask == 0 { return Err("Missing motor port".into()) }
+    Ok(mask)
+}
+
+fn led_pattern(name: &str) -> Result<LedPattern> {
+    if let Ok(code) = name.parse::<u8>() {
+        return LedPattern::from_code(code).ok_or(format!("Invalid LED pattern: {name}").into());
+    }
+    let names = ["off", "green", "red", "orange", "green-flash", "red-flash", "orange-flash", "green-pulse", "red-pulse", "orange-pulse"];
+    names.iter().position(|n| n.eq_ignore_ascii_case(name))
+        .map(|i| LedPattern::ALL[i])
+        .ok_or(format!("Invalid LED pattern: {name}").into())
+}
+
+// Parse P1 (ASCII) or P4 (binary) PBM into 0 / 1 pixels
+fn parse_pbm(bytes: &[u8]) -> Result<Vec<u8>> {
+    let mut pos = 0;
+    let mut header = vec![];
+    while header.len() < 3 {
+        while pos < bytes.len() && (bytes[pos].is_ascii_whitespace() || bytes[pos] == b'#') {
+            if bytes[pos] == b'#' {
+                while pos < bytes.len() && bytes[pos] != b'\n' { pos += 1 }
+            }
+            pos += 1;
+        }
+        let start = pos;
+        while pos < bytes.len() && !bytes[pos].is_ascii_whitespace() { pos += 1 }
+        if start == pos { return Err("Truncated PBM header".into()) }
+        header.push(String::from_utf8_lossy(&bytes[start..pos]).to_string());
+    }
+    if header[1] != "178" || header[2] != "128" { return Err(format!("Image must be 178x128, got {}x{}", header[1], header[2]).into()) }
+    let image: Vec<u8> = match header[0].as_str() {
+        "P1" => bytes[pos..].iter().filter_map(|b| match b { b'0' => Some(0), b'1' => Some(1), _ => None }).collect(),
+        "P4" => bytes.get(pos + 1..).unwrap_or(&[]).chunks(23)
+            .flat_map(|row| (0..178).map(move |x| row.get(x / 8).map_or(0, |b| (b >> (7 - x % 8)) & 1)))
+            .collect(),
+        other => return Err(format!("Unsupported PBM format: {other}").into())
+    };
+    if image.len() < 178 * 128 { return Err("Truncated PBM data".into()) }
+    Ok(image[..178 * 128].to_vec())
+}
+
+// Line editor helper, completing OpCode & sub-command names
+struct Completion;
+
+impl Completer for Completion {
+    type Candidate = String;
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(complete(line, pos))
+    }
+}
+
+impl Hinter for Completion {
+    type Hint = String;
+}
+
+impl Highlighter for Completion {}
+
+impl Validator for Completion {}
+
+impl Helper for Completion {}
+
+fn repl(client: Client<Box<dyn Transport>>) -> Result<()> {
+    let mut editor = Editor::<Completion, DefaultHistory>::new()?;
+    editor.set_helper(Some(Completion));
+    let history = env::home_dir().map(|home| home.join(".ev3dc_history"));
+    if let Some(path) = &history { let _ = editor.load_history(path); }
+    let mut shell = Shell::new(client);
+    loop {
+        let line = match editor.readline("ev3> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) => break,
+            Err(err) => return Err(err.into())
+        };
+        let line = line.trim();
+        if line.is_empty() { continue }
+        editor.add_history_entry(line)?;
+        match line {
+            "exit" | "quit" => break,
+            "help" => println!("{REPL_HELP}"),
+            _ => match shell.eval(line) {
+                Ok(out) => println!("{out}"),
+                Err(err) => eprintln!("Error: {err}")
+            }
+        }
+    }
+    if let Some(path) = &history { editor.save_history(path)?; }
+    Ok(())
+}
+
+fn execute(client: &mut Client<Box<dyn Transport>>, args: &[String]) -> Result<Value> {
+    let mut cmd = Command::new();
+    let command = arg(args, 0, "command")?;
+    Ok(match command {
+        "info" => {
+            let (byte, query) = brick_info(&mut cmd)?;
+            let info = query.parse(direct(client, &mut cmd, byte)?.memory())?;
+            fields(vec![
+                ("name", text(info.name)),
+                ("firmware", text(info.firmware)),
+                ("firmware_build", text(info.fw_build)),
+                ("hardware", text(info.hardware)),
+                ("os", text(info.os)),
+                ("os_build", text(info.os_build)),
+                ("bluetooth", text(info.bt_mac)),
+                ("memory_total", Value::Number(info.mem_total as f64)),
+                ("memory_free", Value::Number(info.mem_free as f64)),
+                ("voltage", Value::Number(info.voltage as f64)),
+                ("current", Value::Number(info.current as f64)),
+                ("battery", Value::Number(info.battery as f64))
+            ])
+        }
+        "ports" => {
+            let mut byte = ChainByte::new();
+            byte.push(0x98) // opInput_Device_List
+                .add(encode(LC1(32))?)
+                .add(cmd.allocate(DataType::DATAN(32), true)?)
+                .add(cmd.allocate(DataType::DATA8, true)?);
+            let rep = direct(client, &mut cmd, byte.bytes)?;
+            let ports = port_read(rep.memory().get(..32).ok_or("Short reply")?, 0)?;
+            let names = ["1", "2", "3", "4", "A", "B", "C", "D"];
+            Value::Object(names.iter().zip(ports).map(|(n, p)| (n.to_string(), text(device_id(p)))).collect())
+        }
+        "ls" => {
+            let list = client.list(args.get(1).map_or("../prjs/", |a| a.as_str()))?;
+            Value::List(list.lines().filter(|l| !l.is_empty()).map(text).collect())
+        }
+        "upload" => {
+            let remote = arg(args, 1, "remote path")?;
+            let local = args.get(2).cloned().unwrap_or(Path::new(remote).file_name().ok_or("Invalid remote path")?.to_string_lossy().to_string());
+            let data = client.upload(remote)?;
+            fs::write(&local, &data)?;
+            fields(vec![("file", text(local)), ("bytes", Value::Number(data.len() as f64))])
+        }
+        "download" => {
+            let data = fs::read(arg(args, 1, "local path")?)?;
+            client.download(arg(args, 2, "remote path")?, &data)?;
+            fields(vec![("bytes", Value::Number(data.len() as f64))])
+        }
+        "rm" => {
+            client.system(&delete_file(arg(args, 1, "path")?)?)?;
+            text("ok")
+        }
+        "run" => {
+            let byte = start_program(&mut cmd, Slot::User, arg(args, 1, "path")?, false)?;
+            direct(client, &mut cmd, byte)?;
+            text("ok")
+        }
+        "stop" => {
+            direct(client, &mut cmd, stop_program(Slot::User)?)?;
+            text("ok")
+        }
+        "beep" => {
+            let freq: i16 = number(args, 1, "frequency", Some(1000))?;
+            let ms: i16 = number(args, 2, "duration", Some(200))?;
+            let volume: i8 = number(args, 3, "volume", Some(50))?;
+            let mut byte = ChainByte::new();
+            byte.add(vec![0x94, 0x01]) // opSound TONE
+                .add(encode(LC1(volume))?)
+                .add(encode(LC2(freq))?)
+                .add(encode(LC2(ms))?);
+            direct(client, &mut cmd, byte.bytes)?;
+            text("ok")
+        }
+        "led" => {
+            direct(client, &mut cmd, led_pattern(arg(args, 1, "pattern")?)?.bytecode())?;
+            text("ok")
+        }
+        "motor" => {
+            let ports = motor_ports(arg(args, 1, "ports")?)?;
+            let byte = match arg(args, 2, "speed")? {
+                "stop" => stop_motor(ports as u16, 0, false)?,
+                _ => {
+                    let speed: i8 = number(args, 2, "speed", None)?;
+                    let mut seq = Sequence::new();
+                    seq.then(motor_speed(ports, speed, 0)?);
+                    if args.len() > 3 {
+                        seq.wait(Duration::from_millis(number(args, 3, "duration", None)?))
+                            .then(stop_motor(ports as u16, 0, false)?);
+                    }
+                    // Reply arrives after the wait
+                    client.timeout += seq.duration();
+                    seq.compile(&mut cmd)?
+                }
+            };
+            direct(client, &mut cmd, byte)?;
+            text("ok")
+        }
+        "sensor" => {
+            let port: i8 = number(args, 1, "port", None)?;
+            if !(1..=4).contains(&port) { return Err(format!("Invalid sensor port: {port}").into()) }
+            let mode: i8 = number(args, 2, "mode", Some(-1))?;
+            let mut byte = ChainByte::new();
+            byte.push(0x9D) // opInput_ReadSI
+                .add(encode(LC0(0))?)
+                .add(encode(LC0(port - 1))?)
+                .add(encode(LC0(0))?)
+                .add(encode(LC1(mode))?)
+                .add(cmd.allocate(DataType::DATAF, true)?);
+            let rep = direct(client, &mut cmd, byte.bytes)?;
+            let raw: [u8; 4] = rep.memory().get(..4).ok_or("Short reply")?.try_into()?;
+            Value::Number(f32::from_le_bytes(raw) as f64)
+        }
+        "screen" => {
+            let image = parse_pbm(&fs::read(arg(args, 1, "image")?)?)?;
+            let mut clear = ChainByte::new();
+            clear.add(vec![0x84, 0x13]) // opUI_Draw FILLWINDOW
+                .add(encode(LC0(0))?)
+                .add(encode(LC0(0))?)
+                .add(encode(LC0(0))?);
+            direct(client, &mut cmd, clear.bytes)?;
+            for packet in package_bytes(&printer(&run_length(&image)?)) {
+                direct(client, &mut cmd, packet)?;
+            }
+            direct(client, &mut cmd, vec![0x84, 0x00])?; // opUI_Draw UPDATE
+            text("ok")
+        }
+        "raw" => {
+            let hex = arg(args, 1, "bytecode")?;
+            if !hex.len().is_multiple_of(2) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(format!("Invalid bytecode, expect pairs of hex digits: {hex}").into());
+            }
+            let byte = (0..hex.len() / 2).map(|i| u8::from_str_radix(&hex[2 * i..2 * i + 2], 16))
+                .collect::<std::result::Result<Vec<u8>, _>>()?;
+            let global: usize = number(args, 2, "global size", Some(0))?;
+            if global > 0 { cmd.allocate(DataType::DATAN(global), true)?; }
+            let rep = direct(client, &mut cmd, byte)?;
+            fields(vec![
+                ("sent", text(cmd.gen_bytes().iter().map(|b| format!("{b:02X}")).collect::<String>())),
+                ("memory", text(rep.memory().iter().map(|b| format!("{b:02X}")).collect::<String>()))
+            ])
+        }
+        other => return Err(format!("Unknown command: {other}\n\n{USAGE}").into())
+    })
+}
+
+fn main() -> ExitCode {
+    let mut target = Target::Usb(None);
+    let mut json = false;
+    let mut args = vec![];
+    let mut argv = env::args().skip(1).peekable();
+    while let Some(a) = argv.next() {
+        match a.as_str() {
+            "-h" | "--help" => { println!("{USAGE}"); return ExitCode::SUCCESS }
+            "--json" => json = true,
+            "--usb" => target = Target::Usb(argv.next_if(|a| a.len() == 12 && a.chars().all(|c| c.is_ascii_hexdigit()))),
+            "--bt" => match argv.next() {
+                Some(path) => target = Target::Bluetooth(path),
+                None => { eprintln!("Missing Bluetooth device\n\n{USAGE}"); return ExitCode::FAILURE }
+            }
+            "--mock" => target = Target::Mock,
+            "--ip" => match argv.next() {
+                Some(addr) => target = match target {
+                    Target::Usb(Some(serial)) | Target::Wifi(_, serial) => Target::Wifi(addr, serial),
+                    _ => Target::Wifi(addr, String::new())
+                },
+                None => { eprintln!("Missing IP address\n\n{USAGE}"); return ExitCode::FAILURE }
+            }
+            "--serial" => {
+                let Some(serial) = argv.next() else { eprintln!("Missing serial number\n\n{USAGE}"); return ExitCode::FAILURE };
+                target = match target {
+                    Target::Wifi(addr, _) => Target::Wifi(addr, serial),
+                    _ => Target::Usb(Some(serial))
+                };
+            }
+            _ => args.push(a)
+        }
+    }
+    if args.is_empty() { eprintln!("{USAGE}"); return ExitCode::FAILURE }
+    let result = connect(&target).and_then(|mut client| match args[0].as_str() {
+        "repl" => repl(client).map(|_| None),
+        _ => execute(&mut client, &args).map(Some)
+    });
+    match result {
+        Ok(value) => {
+            if let Some(value) = value { println!("{}", if json { value.json() } else { value.text() }); }
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            if json { println!("{{\"error\":{}}}", json_string(&err.to_string())) } else { eprintln!("Error: {err}") }
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! Brick-side datalog.
+//!
+//! [`Datalog`] generate small program that samples ports with opInput_ReadSI and writes them with
+//! opFile WRITE_LOG, so sample timing is kept on brick. Run it in user slot, stop it by creating its
+//! stop file, then upload the log and parse it with [`LogData::parse`].
+//! # Example
+//! ```
+//! use std::time::Duration;
+//! use ev3_dc::datalog::{ Datalog, LogData };
+//!
+//! let log = Datalog::new("../prjs/log/run")
+//!     .port(0) // Sensor port 1
+//!     .port(16) // Motor port A
+//!     .interval(Duration::from_millis(10))
+//!     .duration(Duration::from_secs(5))
+//!     .clone();
+//! let image = log.image().unwrap();
+//! assert_eq!(&image[..4], b"LEGO");
+//! // Download image, run it in user slot and wait for it to stop. Then upload log file
+//! println!("{}", log.log_path());
+//! let data = LogData::parse(b"Time\tS1\tMA\r\n0\t1.0\t0\r\n10\t1.0\t2\r\n").unwrap();
+//! assert_eq!(data.columns, vec!["Time", "S1", "MA"]);
+//! assert_eq!(data.rows[1].values, vec![1.0, 2.0]);
+//! assert_eq!(data.to_csv(), "Time,S1,MA\n0,1,0\n10,1,2\n");
+//! ```
+
+use alloc::{ format, string::{ String, ToString }, vec, vec::Vec };
+use core::time::Duration;
+use crate::{ Command, ValError };
+use crate::asm::assemble;
+use crate::funcs::{ file_close, file_open, FileMode };
+use crate::rbf::RbfBuilder;
+
+/// Extension firmware appends to datalog file name
+pub const LOG_EXT: &str = ".rdf";
+
+/// Datalog program configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Datalog {
+    path: String,
+    ports: Vec<u8>,
+    interval: Duration,
+    duration: Duration,
+}
+
+impl Datalog {
+    /// Create datalog writing to `path` (without extension). Default interval is 100 ms, without time limit
+    pub fn new(path: &str) -> Self {
+        Datalog { path: path.to_string(), ports: vec![], interval: Duration::from_millis(100), duration: Duration::ZERO }
+    }
+    /// Add port to sample. Sensor ports are 0 - 3, motor ports are 16 - 19
+    pub fn port(&mut self, port: u8) -> &mut Self {
+        self.ports.push(port);
+        self
+    }
+    /// Set sample interval
+    pub fn interval(&mut self, interval: Duration) -> &mut Self {
+        self.interval = interval;
+        self
+    }
+    /// Set time limit. Zero log until stopped
+    pub fn duration(&mut self, duration: Duration) -> &mut Self {
+        self.duration = duration;
+        self
+    }
+    /// Get path of log file on brick
+    pub fn log_path(&self) -> String {
+        format!("{}{LOG_EXT}", self.path)
+    }
+    /// Get path of stop file. Program stop once it exists
+    pub fn stop_path(&self) -> String {
+        format!("{}.stop", self.path)
+    }
+    /// Get column names, time first
+    pub fn columns(&self) -> Vec<String> {
+        let mut columns = vec!["Time".to_string()];
+        columns.extend(self.ports.iter().map(|port| match port {
+            0..=3 => format!("S{}", port + 1),
+            16..=19 => format!("M{}", (b'A' + port - 16) as char),
+            _ => format!("P{port}")
+        }));
+        columns
+    }
+    /// Generate LMS assembler source of datalog program
+    pub fn source(&self) -> Result<String, ValError> {
+        if self.ports.is_empty() || self.ports.len() > 16 {
+            return Err(ValError::InvalidRange(self.ports.len() as i32, 1, 16));
+        }
+        if let Some(port) = self.ports.iter().find(|p| !matches!(p, 0..=3 | 16..=19)) {
+            return Err(ValError::InvalidValue(*port as i32, 0));
+        }
+        if self.path.is_empty() || self.path.contains(['\'', ',', '\0']) {
+            return Err(ValError::InvalidName(self.path.clone()));
+        }
+        let millis = |time: Duration| i32::try_from(time.as_millis())
+            .map_err(|_| ValError::PosOverflow(time.as_millis().min(u32::MAX as u128) as u32, i32::MAX as u32));
+        let interval = millis(self.interval)?;
+        let duration = millis(self.duration)?;
+        let mut src = String::from("vmthread MAIN\n{\n    HANDLE Log\n    DATA32 Start\n    DATA32 Time\n    DATA32 Timer\n    DATA8 Stop\n");
+        for i in 0..self.ports.len() {
+            src += &format!("    DATAF V{i}\n");
+        }
+        src += &format!("    TIMER_READ(Start)\n    FILE(OPEN_LOG, '{}', 0, 0, 0, {interval}, {duration}, '{}', Log)\nLoop:\n",
+            self.path, self.columns().join(" "));
+        src += "    TIMER_READ(Time)\n    SUB32(Time, Start, Time)\n";
+        for (i, port) in self.ports.iter().enumerate() {
+            src += &format!("    INPUT_READSI(0, {port}, 0, -1, V{i})\n");
+        }
+        src += &format!("    FILE(WRITE_LOG, Log, Time, {}, V0)\n", self.ports.len());
+        src += &format!("    FILENAME(EXIST, '{}', Stop)\n    JR_TRUE(Stop, Done)\n", self.stop_path());
+        src += &format!("    TIMER_WAIT({interval}, Timer)\n    TIMER_READY(Timer)\n");
+        if duration > 0 {
+            src += &format!("    JR_LT32(Time, {duration}, Loop)\n");
+        } else {
+            src += "    JR(Loop)\n";
+        }
+        src += &format!("Done:\n    FILE(CLOSE_LOG, Log, '{}')\n    FILE(REMOVE, '{}')\n}}\n", self.path, self.stop_path());
+        Ok(src)
+    }
+    /// Generate RBF image of datalog program
+    pub fn image(&self) -> Result<Vec<u8>, ValError> {
+        Ok(RbfBuilder::from_program(&assemble(&self.source()?)?).build())
+    }
+    /// Create stop file. Allocates local handle
+    pub fn stop(&self, cmd: &mut Command) -> Result<Vec<u8>, ValError> {
+        let (mut byte, handle, _) = file_open(cmd, &self.stop_path(), FileMode::Write)?;
+        byte.extend(file_close(&handle)?);
+        Ok(byte)
+    }
+}
+
+/// Datalog row
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LogRow {
+    /// Time since start in ms
+    pub time: f32,
+    /// Sampled values in column order
+    pub values: Vec<f32>,
+}
+
+/// Parsed datalog file
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LogData {
+    /// Column names from header, time first. Empty if file has no header
+    pub columns: Vec<String>,
+    /// Rows in file order
+    pub rows: Vec<LogRow>,
+}
+
+impl LogData {
+    /// Parse tab or space separated datalog text. First non-numeric line is header, other non-numeric lines are skipped
+    pub fn parse(bytes: &[u8]) -> Result<Self, ValError> {
+        let text = core::str::from_utf8(bytes).map_err(|err| ValError::Parse(err.valid_up_to(), "Invalid UTF-8".to_string()))?;
+        let mut data = LogData::default();
+        for line in text.lines() {
+            let fields: Vec<&str> = line.split(['\t', ' ', ',']).filter(|f| !f.is_empty()).collect();
+            if fields.is_empty() { continue }
+            let numbers: Option<Vec<f32>> = fields.iter().map(|f| f.parse().ok()).collect();
+            match numbers {
+                Some(numbers) => data.rows.push(LogRow { time: numbers[0], values: numbers[1..].to_vec() }),
+                None if data.columns.is_empty() && data.rows.is_empty() => {
+                    data.columns = fields.iter().map(|f| f.to_string()).collect();
+                }
+                None => {}
+            }
+        }
+        Ok(data)
+    }
+    /// Get rows as CSV, header first when present
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        if !self.columns.is_empty() {
+            csv += &self.columns.join(",");
+            csv.push('\n');
+        }
+        for row in &self.rows {
+            let mut fields = vec![row.time.to_string()];
+            fields.extend(row.values.iter().map(|v| v.to_string()));
+            csv += &fields.join(",");
+            csv.push('\n');
+        }
+        csv
+    }
+}
@@ -0,0 +1,210 @@
+//! Error types for ev3-dc
+//!
+//! [`ValError`] is returned by bytecode builders and helpers in [`crate::funcs`] & [`crate::utils`]. \
+//! [`Error`] wraps it together with transport, reply and system command failures.
+//! Every wrapped error is available through [`core::error::Error::source`].
+//!
+//! # Example
+//! Convert encoding error into [`Error`]
+//! ```
+//! use ev3_dc::{ encode, Encoding::*, Error };
+//!
+//! fn speed(val: i8) -> Result<Vec<u8>, Error> {
+//!     Ok(encode(LC1(val))?)
+//! }
+//! assert!(speed(50).is_ok());
+//! ```
+
+use alloc::string::String;
+use core::error::Error as StdError;
+use core::fmt;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::io;
+use displaystr::display;
+use crate::{ DataType, MemScope };
+
+#[derive(Debug)]
+#[display]
+#[non_exhaustive]
+/// Value error. Returned by encoding, allocation and bytecode helpers
+pub enum ValError {
+    // Error for encoding
+    /// [`crate::encode`] failed to encode overflowed value
+    PosOverflow(u32, u32) = "Encode Error: Value {_0} overflowed {_1}",
+    /// [`crate::encode`] failed to encode underflowed value
+    NegOverflow(i32, i32) = "Encode Error: Value {_0} overflowed {_1}",
+    /// [`crate::encode`] failed to encode string containing zero byte
+    InvalidString(String) = "Encode Error: String {_0:?} contains zero byte",
+    // Error for decoding
+    /// [`crate::decode`] found invalid parameter header
+    Decode(u8) = "Decode Error: Invalid parameter header {_0:#04X}",
+    /// [`crate::decode`] ran out of bytes
+    Incomplete(usize, usize) = "Decode Error: Expect {_1} byte(s) got {_0}",
+    /// [`crate::opcode::disassemble`] found unknown OpCode
+    UnknownOpCode(u8) = "Decode Error: Unknown OpCode {_0:#04X}",
+    /// [`crate::opcode::disassemble`] found unknown sub-command of OpCode
+    UnknownSubCode(u8, u8) = "Decode Error: Unknown sub-command {_1} of OpCode {_0:#04X}",
+    /// [`crate::rbf::RbfImage::parse`] found malformed image
+    InvalidImage(String) = "Decode Error: Invalid image, {_0}",
+    // Error for functions
+    /// [`crate::Command::allocate`] failed to allocated variable in memory
+    MemOverflow(usize, u16, u16, MemScope) = "Allocation Error: Cannot allocate {_0} {_3} byte(s). Memory: {_1}/{_2}",
+    /// Value isn't in valid range
+    InvalidRange(i32, i32, i32) = "Invalid Range: Expect {_1} - {_2} got {_0}",
+    /// Value isn't valid
+    InvalidValue(i32, i32) = "Invalid Value: Expect {_1} got {_0}",
+    /// Data type isn't supported by operation
+    InvalidType(DataType) = "Invalid Type: {_0} isn't supported",
+    /// Name has invalid length or character
+    InvalidName(String) = "Invalid Name: {_0:?}",
+    /// Name isn't defined
+    Undefined(String) = "Undefined: {_0}",
+    /// Source text failed to parse at position (byte offset or line number)
+    Parse(usize, String) = "Parse Error: {_1} at {_0}"
+}
+
+impl StdError for ValError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[display]
+#[non_exhaustive]
+/// System command reply status
+pub enum SystemStatus {
+    Success = "SUCCESS",
+    UnknownHandle = "UNKNOWN_HANDLE",
+    HandleNotReady = "HANDLE_NOT_READY",
+    CorruptFile = "CORRUPT_FILE",
+    NoHandlesAvailable = "NO_HANDLES_AVAILABLE",
+    NoPermission = "NO_PERMISSION",
+    IllegalPath = "ILLEGAL_PATH",
+    FileExits = "FILE_EXITS",
+    EndOfFile = "END_OF_FILE",
+    SizeError = "SIZE_ERROR",
+    UnknownError = "UNKNOWN_ERROR",
+    IllegalFilename = "ILLEGAL_FILENAME",
+    IllegalConnection = "ILLEGAL_CONNECTION",
+    /// Status code not defined by firmware
+    Unknown(u8) = "UNKNOWN({_0:#04X})",
+}
+
+impl SystemStatus {
+    /// Get status from system reply status byte
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            0x00 => SystemStatus::Success,
+            0x01 => SystemStatus::UnknownHandle,
+            0x02 => SystemStatus::HandleNotReady,
+            0x03 => SystemStatus::CorruptFile,
+            0x04 => SystemStatus::NoHandlesAvailable,
+            0x05 => SystemStatus::NoPermission,
+            0x06 => SystemStatus::IllegalPath,
+            0x07 => SystemStatus::FileExits,
+            0x08 => SystemStatus::EndOfFile,
+            0x09 => SystemStatus::SizeError,
+            0x0A => SystemStatus::UnknownError,
+            0x0B => SystemStatus::IllegalFilename,
+            0x0C => SystemStatus::IllegalConnection,
+            _ => SystemStatus::Unknown(code)
+        }
+    }
+    /// Get system reply status byte
+    pub fn code(&self) -> u8 {
+        match self {
+            SystemStatus::Success => 0x00,
+            SystemStatus::UnknownHandle => 0x01,
+            SystemStatus::HandleNotReady => 0x02,
+            SystemStatus::CorruptFile => 0x03,
+            SystemStatus::NoHandlesAvailable => 0x04,
+            SystemStatus::NoPermission => 0x05,
+            SystemStatus::IllegalPath => 0x06,
+            SystemStatus::FileExits => 0x07,
+            SystemStatus::EndOfFile => 0x08,
+            SystemStatus::SizeError => 0x09,
+            SystemStatus::UnknownError => 0x0A,
+            SystemStatus::IllegalFilename => 0x0B,
+            SystemStatus::IllegalConnection => 0x0C,
+            SystemStatus::Unknown(code) => *code
+        }
+    }
+}
+
+impl StdError for SystemStatus {}
+
+#[derive(Debug)]
+#[display]
+#[non_exhaustive]
+/// Malformed or unexpected reply
+pub enum ReplyError {
+    /// Reply is shorter than expected
+    Truncated(usize, usize) = "Reply Error: Expect at least {_1} byte(s) got {_0}",
+    /// Reply id doesn't match command id
+    IdMismatch(u16, u16) = "Reply Error: Expect id {_1} got {_0}",
+    /// Reply type byte isn't a known reply type
+    UnknownType(u8) = "Reply Error: Unknown reply type {_0:#04X}",
+    /// Brick returned DIRECT_REPLY_ERROR for command id
+    Direct(u16) = "Reply Error: Direct command {_0} failed",
+}
+
+impl StdError for ReplyError {}
+
+#[derive(Debug)]
+#[non_exhaustive]
+/// ev3_dc Error type
+pub enum Error {
+    /// Encoding, allocation or parameter error
+    Value(ValError),
+    /// Reply couldn't be parsed or reported failure
+    Reply(ReplyError),
+    /// System command returned non-success status
+    System(SystemStatus),
+    /// Transport failed to read or write. Require `std` feature
+    #[cfg(feature = "std")]
+    Io(io::Error),
+    /// No reply arrived in time
+    Timeout(Duration),
+}
+
+// Written by hand, `#[display]` doesn't support `#[cfg]` on variant
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Value(err) => write!(f, "{err}"),
+            Error::Reply(err) => write!(f, "{err}"),
+            Error::System(err) => write!(f, "System Error: {err}"),
+            #[cfg(feature = "std")]
+            Error::Io(err) => write!(f, "I/O Error: {err}"),
+            Error::Timeout(time) => write!(f, "Timeout: No reply after {time:?}")
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Value(err) => Some(err),
+            Error::Reply(err) => Some(err),
+            Error::System(err) => Some(err),
+            #[cfg(feature = "std")]
+            Error::Io(err) => Some(err),
+            Error::Timeout(_) => None
+        }
+    }
+}
+
+impl From<ValError> for Error {
+    fn from(err: ValError) -> Self { Error::Value(err) }
+}
+
+impl From<ReplyError> for Error {
+    fn from(err: ReplyError) -> Self { Error::Reply(err) }
+}
+
+impl From<SystemStatus> for Error {
+    fn from(err: SystemStatus) -> Self { Error::System(err) }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self { Error::Io(err) }
+}
@@ -0,0 +1,365 @@
+//! Host-side expression compiler.
+//!
+//! Compile arithmetic expression over allocated [`Variable`]s into VM arithmetic OpCodes.
+//! Temporaries are allocated as local variables with [`Command::allocate`].
+//!
+//! ### Syntax
+//!  - Integer & float constants: `42`, `-10`, `0.5`
+//!  - Operators: `+ - * /` and parentheses
+//!  - Functions: `min(a, b)`, `max(a, b)`, `abs(x)`, `clamp(x, low, high)`
+//!
+//! Each operation is computed in the widest type of its operands (DATA8 < DATA16 < DATA32 < DATAF),
+//! then converted with opMove into the destination type. Integer operations wrap like the VM does.
+//! # Example
+//! Scale gyro reading into motor power
+//! ```
+//! use ev3_dc::{ Command, DataType::* };
+//! use ev3_dc::expr::compile;
+//!
+//! let mut cmd = Command::new();
+//! cmd.allocate(DATA16, true).unwrap();
+//! cmd.allocate(DATA8, true).unwrap();
+//! let gyro = cmd.variables()[0];
+//! let power = cmd.variables()[1];
+//! let byte = compile("clamp(gyro * 2 - 10, -100, 100)", &[("gyro", gyro)], &power, &mut cmd).unwrap();
+//! println!("Bytecode: {:02X?}", byte);
+//!
+//! // opAdd8 GV0(0) LC0(5) LV0(4), opMove8_8 LV0(4) GV0(1)
+//! let mut cmd = Command::new();
+//! cmd.allocate(DATA8, true).unwrap();
+//! cmd.allocate(DATA8, true).unwrap();
+//! let (speed, out) = (cmd.variables()[0], cmd.variables()[1]);
+//! cmd.allocate(DATA8, false).unwrap();
+//! cmd.allocate(DATA16, false).unwrap();
+//! let byte = compile("speed + 5", &[("speed", speed)], &out, &mut cmd).unwrap();
+//! assert_eq!(byte, vec![0x10, 0x60, 0x05, 0x44, 0x30, 0x44, 0x61]);
+//! ```
+
+use alloc::{ boxed::Box, format, string::{ String, ToString }, vec, vec::Vec };
+use crate::{ encode, Command, DataType, Encoding::*, ValError, Variable };
+use crate::utils::auto_const;
+
+const OP_ADD: u8 = 0x10;
+const OP_SUB: u8 = 0x14;
+const OP_MUL: u8 = 0x18;
+const OP_DIV: u8 = 0x1C;
+const OP_MOVE: u8 = 0x30;
+const OP_CP_LT: u8 = 0x44;
+const OP_CP_GT: u8 = 0x48;
+const OP_SELECT: u8 = 0x5C;
+
+/// Binary operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+/// Built-in function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Func {
+    Min,
+    Max,
+    Abs,
+    Clamp,
+}
+
+/// Expression tree
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// Integer constant
+    Int(i32),
+    /// Float constant
+    Float(f32),
+    /// Named variable
+    Var(String),
+    /// Negation
+    Neg(Box<Expr>),
+    /// Binary operation
+    Bin(BinOp, Box<Expr>, Box<Expr>),
+    /// Function call
+    Call(Func, Vec<Expr>),
+}
+
+/// Compile expression and store result in `dest`. \
+/// Return bytecode
+/// # Example
+/// ```
+/// use ev3_dc::{ Command, DataType::* };
+/// use ev3_dc::expr::compile;
+///
+/// let mut cmd = Command::new();
+/// cmd.allocate(DATA32, true).unwrap();
+/// let dest = cmd.variables()[0];
+/// // opMove32_32 LC4(16777217) GV0(0), exact without float rounding
+/// assert_eq!(compile("16777217", &[], &dest, &mut cmd).unwrap(), vec![0x3A, 0x83, 0x01, 0x00, 0x00, 0x01, 0x60]);
+/// assert!(compile("1 +\u{a0}2", &[], &dest, &mut cmd).is_ok());
+/// ```
+pub fn compile(src: &str, vars: &[(&str, Variable)], dest: &Variable, cmd: &mut Command) -> Result<Vec<u8>, ValError> {
+    Expr::parse(src)?.compile(vars, dest, cmd)
+}
+
+// Rank of numeric type, also opcode offset for ADD8/16/32/F style families
+fn rank(dtype: DataType) -> Result<u8, ValError> {
+    match dtype {
+        DataType::DATA8 => Ok(0),
+        DataType::DATA16 => Ok(1),
+        DataType::DATA32 => Ok(2),
+        DataType::DATAF => Ok(3),
+        _ => Err(ValError::InvalidType(dtype))
+    }
+}
+
+fn wider(a: DataType, b: DataType) -> Result<DataType, ValError> {
+    Ok(if rank(a)? >= rank(b)? { a } else { b })
+}
+
+// Encode constant as parameter of type
+fn constant(val: i32, dtype: DataType) -> Result<Vec<u8>, ValError> {
+    let (min, max) = match dtype {
+        DataType::DATAF => return encode(LCF(val as f32)),
+        DataType::DATA8 => (i8::MIN as i32, i8::MAX as i32),
+        DataType::DATA16 => (i16::MIN as i32, i16::MAX as i32),
+        _ => (i32::MIN, i32::MAX)
+    };
+    if val < min || val > max { return Err(ValError::InvalidRange(val, min, max)) }
+    auto_const(val)
+}
+
+struct Context<'a, 'b> {
+    vars: &'a [(&'b str, Variable)],
+    cmd: &'a mut Command,
+    code: Vec<u8>,
+}
+
+impl Context<'_, '_> {
+    fn var(&self, name: &str) -> Result<Variable, ValError> {
+        self.vars.iter().find(|(n, _)| *n == name).map(|(_, v)| *v)
+            .ok_or(ValError::Undefined(name.to_string()))
+    }
+    // Allocate local temporary and return its address
+    fn temp(&mut self, dtype: DataType) -> Result<Vec<u8>, ValError> {
+        self.cmd.allocate(dtype, false)
+    }
+    // Emit opMove between numeric types. Return destination address
+    fn convert(&mut self, param: Vec<u8>, from: DataType, to: DataType) -> Result<Vec<u8>, ValError> {
+        if from == to { return Ok(param) }
+        let dest = self.temp(to)?;
+        self.code.push(OP_MOVE + 4 * rank(from)? + rank(to)?);
+        self.code.extend(param);
+        self.code.extend(&dest);
+        Ok(dest)
+    }
+    // Emit 2 inputs 1 output operation. Return output address
+    fn op(&mut self, base: u8, dtype: DataType, a: Vec<u8>, b: Vec<u8>) -> Result<Vec<u8>, ValError> {
+        let dest = self.temp(dtype)?;
+        self.code.push(base + rank(dtype)?);
+        self.code.extend(a);
+        self.code.extend(b);
+        self.code.extend(&dest);
+        Ok(dest)
+    }
+    // Emit compare & select, returning `a` if `a <cmp> b` else `b`
+    fn select(&mut self, cmp: u8, dtype: DataType, a: Vec<u8>, b: Vec<u8>) -> Result<Vec<u8>, ValError> {
+        let flag = self.temp(DataType::DATA8)?;
+        self.code.push(cmp + rank(dtype)?);
+        self.code.extend(&a);
+        self.code.extend(&b);
+        self.code.extend(&flag);
+        let dest = self.temp(dtype)?;
+        self.code.push(OP_SELECT + rank(dtype)?);
+        self.code.extend(flag);
+        self.code.extend(a);
+        self.code.extend(b);
+        self.code.extend(&dest);
+        Ok(dest)
+    }
+}
+
+impl Expr {
+    /// Parse expression from text
+    pub fn parse(src: &str) -> Result<Self, ValError> {
+        let mut parser = Parser { src, pos: 0 };
+        let expr = parser.expr()?;
+        parser.skip();
+        if parser.pos < src.len() { return Err(parser.error("Unexpected character")) }
+        Ok(expr)
+    }
+    /// Compile expression and store result in `dest`. \
+    /// Return bytecode
+    pub fn compile(&self, vars: &[(&str, Variable)], dest: &Variable, cmd: &mut Command) -> Result<Vec<u8>, ValError> {
+        let mut cx = Context { vars, cmd, code: vec![] };
+        let dtype = self.dtype(&cx)?;
+        let param = self.emit(dtype, &mut cx)?;
+        let target = dest.encode()?;
+        cx.code.push(OP_MOVE + 4 * rank(dtype)? + rank(dest.dtype)?);
+        cx.code.extend(param);
+        cx.code.extend(target);
+        Ok(cx.code)
+    }
+    // Infer type of expression
+    fn dtype(&self, cx: &Context) -> Result<DataType, ValError> {
+        match self {
+            Expr::Int(val) => Ok(match val {
+                -128..128 => DataType::DATA8,
+                -32768..32768 => DataType::DATA16,
+                _ => DataType::DATA32
+            }),
+            Expr::Float(_) => Ok(DataType::DATAF),
+            Expr::Var(name) => {
+                let dtype = cx.var(name)?.dtype;
+                rank(dtype)?;
+                Ok(dtype)
+            }
+            Expr::Neg(expr) => expr.dtype(cx),
+            Expr::Bin(_, a, b) => wider(a.dtype(cx)?, b.dtype(cx)?),
+            Expr::Call(_, args) => args.iter().try_fold(DataType::DATA8, |acc, arg| wider(acc, arg.dtype(cx)?))
+        }
+    }
+    // Emit code computing expression. Return parameter holding result as `dtype`
+    fn emit(&self, dtype: DataType, cx: &mut Context) -> Result<Vec<u8>, ValError> {
+        let own = self.dtype(cx)?;
+        let param = match self {
+            Expr::Int(val) => return constant(*val, dtype),
+            Expr::Float(val) => return match dtype {
+                DataType::DATAF => encode(LCF(*val)),
+                _ => constant(*val as i32, dtype)
+            },
+            Expr::Var(name) => cx.var(name)?.encode()?,
+            Expr::Neg(expr) => {
+                let val = expr.emit(own, cx)?;
+                cx.op(OP_SUB, own, constant(0, own)?, val)?
+            }
+            Expr::Bin(op, a, b) => {
+                let a = a.emit(own, cx)?;
+                let b = b.emit(own, cx)?;
+                let base = match op {
+                    BinOp::Add => OP_ADD,
+                    BinOp::Sub => OP_SUB,
+                    BinOp::Mul => OP_MUL,
+                    BinOp::Div => OP_DIV
+                };
+                cx.op(base, own, a, b)?
+            }
+            Expr::Call(func, args) => {
+                let mut params = vec![];
+                for arg in args { params.push(arg.emit(own, cx)?); }
+                let mut params = params.into_iter();
+                let mut next = || params.next().ok_or(ValError::InvalidValue(args.len() as i32, func.arity() as i32));
+                match func {
+                    Func::Min => { let (a, b) = (next()?, next()?); cx.select(OP_CP_LT, own, a, b)? }
+                    Func::Max => { let (a, b) = (next()?, next()?); cx.select(OP_CP_GT, own, a, b)? }
+                    Func::Abs => {
+                        let val = next()?;
+                        let neg = cx.op(OP_SUB, own, constant(0, own)?, val.clone())?;
+                        cx.select(OP_CP_GT, own, val, neg)?
+                    }
+                    Func::Clamp => {
+                        let (val, low, high) = (next()?, next()?, next()?);
+                        let val = cx.select(OP_CP_GT, own, val, low)?;
+                        cx.select(OP_CP_LT, own, val, high)?
+                    }
+                }
+            }
+        };
+        cx.convert(param, own, dtype)
+    }
+}
+
+impl Func {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "min" => Some(Func::Min),
+            "max" => Some(Func::Max),
+            "abs" => Some(Func::Abs),
+            "clamp" => Some(Func::Clamp),
+            _ => None
+        }
+    }
+    /// Number of arguments
+    pub fn arity(&self) -> usize {
+        match self {
+            Func::Abs => 1,
+            Func::Min | Func::Max => 2,
+            Func::Clamp => 3
+        }
+    }
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, msg: &str) -> ValError {
+        ValError::Parse(self.pos, msg.to_string())
+    }
+    fn skip(&mut self) {
+        let rest = &self.src[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+    fn eat(&mut self, ch: char) -> bool {
+        self.skip();
+        if self.src[self.pos..].starts_with(ch) {
+            self.pos += ch.len_utf8();
+            true
+        } else { false }
+    }
+    fn take(&mut self, pred: fn(char) -> bool) -> &str {
+        let start = self.pos;
+        let rest = &self.src[start..];
+        self.pos += rest.len() - rest.trim_start_matches(pred).len();
+        &self.src[start..self.pos]
+    }
+    fn expr(&mut self) -> Result<Expr, ValError> {
+        let mut expr = self.term()?;
+        loop {
+            let op = if self.eat('+') { BinOp::Add } else if self.eat('-') { BinOp::Sub } else { return Ok(expr) };
+            expr = Expr::Bin(op, Box::new(expr), Box::new(self.term()?));
+        }
+    }
+    fn term(&mut self) -> Result<Expr, ValError> {
+        let mut expr = self.unary()?;
+        loop {
+            let op = if self.eat('*') { BinOp::Mul } else if self.eat('/') { BinOp::Div } else { return Ok(expr) };
+            expr = Expr::Bin(op, Box::new(expr), Box::new(self.unary()?));
+        }
+    }
+    fn unary(&mut self) -> Result<Expr, ValError> {
+        if !self.eat('-') { return self.primary() }
+        Ok(match self.unary()? {
+            Expr::Int(val) => Expr::Int(-val),
+            Expr::Float(val) => Expr::Float(-val),
+            expr => Expr::Neg(Box::new(expr))
+        })
+    }
+    fn primary(&mut self) -> Result<Expr, ValError> {
+        if self.eat('(') {
+            let expr = self.expr()?;
+            if !self.eat(')') { return Err(self.error("Expect ')'")) }
+            return Ok(expr);
+        }
+        self.skip();
+        let start = self.pos;
+        let num = self.take(|c| c.is_ascii_digit() || c == '.');
+        if !num.is_empty() {
+            let expr = match num.contains('.') {
+                true => num.parse().ok().map(Expr::Float),
+                false => num.parse().ok().map(Expr::Int)
+            };
+            return expr.ok_or(ValError::Parse(start, format!("Invalid number {num}")));
+        }
+        let name = self.take(|c| c.is_ascii_alphanumeric() || c == '_').to_string();
+        if name.is_empty() { return Err(self.error("Expect value")) }
+        if !self.eat('(') { return Ok(Expr::Var(name)) }
+        let func = Func::from_name(&name).ok_or(ValError::Undefined(name))?;
+        let mut args = vec![self.expr()?];
+        while self.eat(',') { args.push(self.expr()?); }
+        if !self.eat(')') { return Err(self.error("Expect ')'")) }
+        if args.len() != func.arity() { return Err(ValError::InvalidValue(args.len() as i32, func.arity() as i32)) }
+        Ok(Expr::Call(func, args))
+    }
+}
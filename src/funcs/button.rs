@@ -0,0 +1,117 @@
+//! Brick buttons
+
+use alloc::{ vec, vec::Vec };
+use crate::{ encode, Command, DataType, Encoding::*, ValError };
+use crate::utils::ChainByte;
+
+/// Brick button
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Button {
+    Up,
+    Enter,
+    Down,
+    Right,
+    Left,
+    Back,
+    /// Any button
+    Any,
+}
+
+impl Button {
+    /// Every button in id order
+    pub const ALL: [Button; 7] = [Button::Up, Button::Enter, Button::Down, Button::Right, Button::Left, Button::Back, Button::Any];
+    /// Get firmware button id
+    pub fn id(&self) -> i8 {
+        match self {
+            Button::Up => 1,
+            Button::Enter => 2,
+            Button::Down => 3,
+            Button::Right => 4,
+            Button::Left => 5,
+            Button::Back => 6,
+            Button::Any => 7
+        }
+    }
+}
+
+fn button_read(cmd: &mut Command, sub: i8, button: Button) -> Result<(Vec<u8>, Vec<DataType>), ValError> {
+    let mut byte = ChainByte::new();
+    byte.push(0x83) // opUI_Button
+        .add(encode(LC0(sub))?)
+        .add(encode(LC0(button.id()))?)
+        .add(cmd.allocate(DataType::DATA8, true)?);
+    Ok((byte.bytes, vec![DataType::DATA8]))
+}
+
+fn button_write(sub: i8, button: Option<Button>) -> Result<Vec<u8>, ValError> {
+    let mut byte = ChainByte::new();
+    byte.push(0x83) // opUI_Button
+        .add(encode(LC0(sub))?);
+    if let Some(button) = button {
+        byte.add(encode(LC0(button.id()))?);
+    }
+    Ok(byte.bytes)
+}
+
+/// Check if button is held down. Reply 1 when pressed
+/// Return bytecodes and vector of `DataType`
+/// # Example
+/// ```
+/// use ev3_dc::Command;
+/// use ev3_dc::funcs::{ button_pressed, Button };
+///
+/// let mut cmd = Command::new();
+/// let (byte, _) = button_pressed(&mut cmd, Button::Enter).unwrap();
+/// assert_eq!(byte, vec![0x83, 0x09, 0x02, 0x60]);
+/// ```
+pub fn button_pressed(cmd: &mut Command, button: Button) -> Result<(Vec<u8>, Vec<DataType>), ValError> {
+    button_read(cmd, 9, button) // PRESSED
+}
+
+/// Check if button was short pressed since last check. Reply 1 when pressed
+/// Return bytecodes and vector of `DataType`
+pub fn button_short_press(cmd: &mut Command, button: Button) -> Result<(Vec<u8>, Vec<DataType>), ValError> {
+    button_read(cmd, 1, button) // SHORTPRESS
+}
+
+/// Check if button was long pressed since last check. Reply 1 when pressed
+/// Return bytecodes and vector of `DataType`
+pub fn button_long_press(cmd: &mut Command, button: Button) -> Result<(Vec<u8>, Vec<DataType>), ValError> {
+    button_read(cmd, 2, button) // LONGPRESS
+}
+
+/// Check if button was pressed & released since last check. Reply 1 when bumped
+/// Return bytecodes and vector of `DataType`
+pub fn button_bumped(cmd: &mut Command, button: Button) -> Result<(Vec<u8>, Vec<DataType>), ValError> {
+    button_read(cmd, 14, button) // GET_BUMPED
+}
+
+/// Wait until any button is pressed
+pub fn button_wait() -> Result<Vec<u8>, ValError> {
+    button_write(3, None) // WAIT_FOR_PRESS
+}
+
+/// Clear pending button presses
+pub fn button_flush() -> Result<Vec<u8>, ValError> {
+    button_write(4, None) // FLUSH
+}
+
+/// Simulate button press. Release with [`button_release`]
+/// # Example
+/// Open selected menu item
+/// ```
+/// use ev3_dc::funcs::{ button_press, button_release, Button };
+///
+/// let mut byte = button_press(Button::Enter).unwrap();
+/// byte.extend(button_release(Button::Enter).unwrap());
+/// assert_eq!(byte, vec![0x83, 0x05, 0x02, 0x83, 0x06, 0x02]);
+/// ```
+pub fn button_press(button: Button) -> Result<Vec<u8>, ValError> {
+    button_write(5, Some(button)) // PRESS
+}
+
+/// Simulate button release
+pub fn button_release(button: Button) -> Result<Vec<u8>, ValError> {
+    button_write(6, Some(button)) // RELEASE
+}
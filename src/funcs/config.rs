@@ -0,0 +1,143 @@
+//! Brick configuration
+
+use alloc::{ string::ToString, vec, vec::Vec };
+use crate::{ encode, Command, DataType, Encoding::*, ValError };
+use crate::utils::ChainByte;
+
+/// Longest brick name accepted by [`set_brick_name`]
+pub const NAME_MAX: usize = 12;
+
+/// Wireless hardware
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Radio {
+    Bluetooth,
+    Wifi,
+}
+
+impl Radio {
+    fn id(&self) -> i8 {
+        match self {
+            Radio::Bluetooth => 2, // HW_BT
+            Radio::Wifi => 3 // HW_WIFI
+        }
+    }
+}
+
+/// Set brick name. Accept 1 - 12 ASCII letters, digits, `-` or `_`
+/// # Example
+/// ```
+/// use ev3_dc::funcs::set_brick_name;
+///
+/// assert_eq!(set_brick_name("EV3-01").unwrap(), vec![0xD4, 0x08, 0x84, b'E', b'V', b'3', b'-', b'0', b'1', 0x00]);
+/// assert!(set_brick_name("").is_err());
+/// assert!(set_brick_name("Robot Number 1").is_err());
+/// assert!(set_brick_name("Bö").is_err());
+/// ```
+pub fn set_brick_name(name: &str) -> Result<Vec<u8>, ValError> {
+    if name.is_empty() || name.len() > NAME_MAX {
+        return Err(ValError::InvalidRange(name.len() as i32, 1, NAME_MAX as i32));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(ValError::InvalidName(name.to_string()));
+    }
+    let mut byte = ChainByte::new();
+    byte.push(0xD4) // opCom_Set
+        .add(encode(LC0(8))?) // SET_BRICKNAME
+        .add(encode(LCS(name))?);
+    Ok(byte.bytes)
+}
+
+/// Turn wireless hardware on or off
+/// # Example
+/// ```
+/// use ev3_dc::funcs::{ set_radio, Radio };
+///
+/// assert_eq!(set_radio(Radio::Bluetooth, true).unwrap(), vec![0xD4, 0x01, 0x02, 0x01]);
+/// ```
+pub fn set_radio(radio: Radio, on: bool) -> Result<Vec<u8>, ValError> {
+    let mut byte = ChainByte::new();
+    byte.push(0xD4) // opCom_Set
+        .add(encode(LC0(1))?) // SET_ON_OFF
+        .add(encode(LC0(radio.id()))?)
+        .add(encode(LC0(on as i8))?);
+    Ok(byte.bytes)
+}
+
+/// Set whether brick is visible to wireless discovery
+/// # Example
+/// ```
+/// use ev3_dc::funcs::{ set_visible, Radio };
+///
+/// assert_eq!(set_visible(Radio::Wifi, false).unwrap(), vec![0xD4, 0x02, 0x03, 0x00]);
+/// ```
+pub fn set_visible(radio: Radio, visible: bool) -> Result<Vec<u8>, ValError> {
+    let mut byte = ChainByte::new();
+    byte.push(0xD4) // opCom_Set
+        .add(encode(LC0(2))?) // SET_VISIBLE
+        .add(encode(LC0(radio.id()))?)
+        .add(encode(LC0(visible as i8))?);
+    Ok(byte.bytes)
+}
+
+/// Set sound volume in percent (0 - 100)
+/// # Example
+/// ```
+/// use ev3_dc::funcs::set_volume;
+///
+/// assert_eq!(set_volume(75).unwrap(), vec![0x7C, 0x05, 0x81, 75]);
+/// assert!(set_volume(101).is_err());
+/// ```
+pub fn set_volume(volume: u8) -> Result<Vec<u8>, ValError> {
+    if volume > 100 { return Err(ValError::InvalidRange(volume as i32, 0, 100)) }
+    let mut byte = ChainByte::new();
+    byte.push(0x7C) // opInfo
+        .add(encode(LC0(5))?) // SET_VOLUME
+        .add(encode(LC1(volume as i8))?);
+    Ok(byte.bytes)
+}
+
+/// Set sleep timer in minutes (0 - 120). 0 disable sleep
+/// # Example
+/// ```
+/// use ev3_dc::funcs::set_sleep;
+///
+/// assert_eq!(set_sleep(30).unwrap(), vec![0x7C, 0x07, 0x81, 30]);
+/// assert!(set_sleep(121).is_err());
+/// ```
+pub fn set_sleep(minutes: u8) -> Result<Vec<u8>, ValError> {
+    if minutes > 120 { return Err(ValError::InvalidRange(minutes as i32, 0, 120)) }
+    let mut byte = ChainByte::new();
+    byte.push(0x7C) // opInfo
+        .add(encode(LC0(7))?) // SET_MINUTES
+        .add(encode(LC1(minutes as i8))?);
+    Ok(byte.bytes)
+}
+
+/// Get sound volume in percent
+/// Return bytecodes and vector of `DataType`
+/// # Example
+/// ```
+/// use ev3_dc::Command;
+/// use ev3_dc::funcs::{ get_sleep, get_volume };
+///
+/// let mut cmd = Command::new();
+/// assert_eq!(get_volume(&mut cmd).unwrap().0, vec![0x7C, 0x04, 0x60]);
+/// assert_eq!(get_sleep(&mut cmd).unwrap().0, vec![0x7C, 0x06, 0x61]);
+/// ```
+pub fn get_volume(cmd: &mut Command) -> Result<(Vec<u8>, Vec<DataType>), ValError> {
+    let mut byte = ChainByte::new();
+    byte.push(0x7C) // opInfo
+        .add(encode(LC0(4))?) // GET_VOLUME
+        .add(cmd.allocate(DataType::DATA8, true)?);
+    Ok((byte.bytes, vec![DataType::DATA8]))
+}
+
+/// Get sleep timer in minutes
+/// Return bytecodes and vector of `DataType`
+pub fn get_sleep(cmd: &mut Command) -> Result<(Vec<u8>, Vec<DataType>), ValError> {
+    let mut byte = ChainByte::new();
+    byte.push(0x7C) // opInfo
+        .add(encode(LC0(6))?) // GET_MINUTES
+        .add(cmd.allocate(DataType::DATA8, true)?);
+    Ok((byte.bytes, vec![DataType::DATA8]))
+}
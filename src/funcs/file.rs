@@ -0,0 +1,220 @@
+//! File I/O inside direct commands (opFile)
+
+use alloc::{ vec, vec::Vec };
+use crate::{ encode, Command, DataType, Encoding::*, ValError };
+use crate::utils::{ ChainByte, auto_const };
+
+/// File open mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileMode {
+    /// Read from start. Also reply file size
+    Read,
+    /// Create or truncate
+    Write,
+    /// Create or append to end
+    Append,
+}
+
+/// Delimiter after each text read or written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delimiter {
+    None,
+    Tab,
+    Space,
+    Return,
+    Colon,
+    Comma,
+    LineFeed,
+    CrLf,
+}
+
+impl Delimiter {
+    fn id(&self) -> i8 {
+        match self {
+            Delimiter::None => 0,
+            Delimiter::Tab => 1,
+            Delimiter::Space => 2,
+            Delimiter::Return => 3,
+            Delimiter::Colon => 4,
+            Delimiter::Comma => 5,
+            Delimiter::LineFeed => 6,
+            Delimiter::CrLf => 7
+        }
+    }
+}
+
+/// File handle stored in DATA16 variable. Returned by [`file_open`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileHandle {
+    var: Vec<u8>,
+}
+
+impl FileHandle {
+    /// Allocate handle variable. Global handle can be read from reply and reused with [`FileHandle::from_const`]
+    pub fn alloc(cmd: &mut Command, global: bool) -> Result<Self, ValError> {
+        Ok(FileHandle { var: cmd.allocate(DataType::DATA16, global)? })
+    }
+    /// Use handle number from previous reply
+    pub fn from_const(handle: u16) -> Result<Self, ValError> {
+        Ok(FileHandle { var: auto_const(handle as i32)? })
+    }
+    /// Get encoded variable
+    pub fn var(&self) -> &[u8] {
+        &self.var
+    }
+}
+
+/// Open file. Allocates local handle, and global DATA32 file size for [`FileMode::Read`]
+/// Return bytecodes, handle and vector of `DataType`
+/// # Example
+/// Read first line of config file
+/// ```
+/// use ev3_dc::Command;
+/// use ev3_dc::funcs::{ file_open, file_read_text, file_close, Delimiter, FileMode };
+///
+/// let mut cmd = Command::new();
+/// let (open, handle, _) = file_open(&mut cmd, "../prjs/cfg.txt", FileMode::Read).unwrap();
+/// let (read, _) = file_read_text(&mut cmd, &handle, Delimiter::LineFeed, 32).unwrap();
+/// cmd.bytecode = [open, read, file_close(&handle).unwrap()].concat();
+/// assert_eq!(cmd.reserved_bytes(), 4 + 32); // size, line
+/// assert_eq!(cmd.local_bytes(), 2); // handle
+/// println!("SENT: {:02X?}", cmd.gen_bytes());
+/// ```
+pub fn file_open(cmd: &mut Command, path: &str, mode: FileMode) -> Result<(Vec<u8>, FileHandle, Vec<DataType>), ValError> {
+    let handle = FileHandle::alloc(cmd, false)?;
+    let mut byte = ChainByte::new();
+    byte.push(0xC0) // opFile
+        .add(encode(LC0(match mode {
+            FileMode::Append => 0, // OPEN_APPEND
+            FileMode::Read => 1, // OPEN_READ
+            FileMode::Write => 2 // OPEN_WRITE
+        }))?)
+        .add(encode(LCS(path))?)
+        .add(handle.var.clone());
+    let mut dtypes = vec![];
+    if let FileMode::Read = mode {
+        byte.add(cmd.allocate(DataType::DATA32, true)?);
+        dtypes.push(DataType::DATA32);
+    }
+    Ok((byte.bytes, handle, dtypes))
+}
+
+/// Read text until delimiter. `length` include zero terminator
+/// Return bytecodes and vector of `DataType`
+pub fn file_read_text(cmd: &mut Command, handle: &FileHandle, delimiter: Delimiter, length: u8) -> Result<(Vec<u8>, Vec<DataType>), ValError> {
+    if length == 0 { return Err(ValError::InvalidRange(0, 1, u8::MAX as i32)) }
+    let dtype = DataType::DATAS(length as usize - 1);
+    let mut byte = ChainByte::new();
+    byte.push(0xC0) // opFile
+        .add(encode(LC0(5))?) // READ_TEXT
+        .add(handle.var.clone())
+        .add(encode(LC0(delimiter.id()))?)
+        .add(auto_const(length as i32)?)
+        .add(cmd.allocate(dtype, true)?);
+    Ok((byte.bytes, vec![dtype]))
+}
+
+/// Write text followed by delimiter
+/// # Example
+/// Append line to log
+/// ```
+/// use ev3_dc::Command;
+/// use ev3_dc::funcs::{ file_open, file_write_text, file_close, Delimiter, FileMode };
+///
+/// let mut cmd = Command::new();
+/// let (open, handle, _) = file_open(&mut cmd, "../prjs/log.txt", FileMode::Append).unwrap();
+/// let write = file_write_text(&handle, Delimiter::LineFeed, "ok").unwrap();
+/// assert_eq!(write, vec![0xC0, 0x06, 0x40, 0x06, 0x84, b'o', b'k', 0x00]);
+/// assert_eq!(file_close(&handle).unwrap(), vec![0xC0, 0x07, 0x40]);
+/// ```
+pub fn file_write_text(handle: &FileHandle, delimiter: Delimiter, text: &str) -> Result<Vec<u8>, ValError> {
+    let mut byte = ChainByte::new();
+    byte.push(0xC0) // opFile
+        .add(encode(LC0(6))?) // WRITE_TEXT
+        .add(handle.var.clone())
+        .add(encode(LC0(delimiter.id()))?)
+        .add(encode(LCS(text))?);
+    Ok(byte.bytes)
+}
+
+/// Close file
+pub fn file_close(handle: &FileHandle) -> Result<Vec<u8>, ValError> {
+    let mut byte = ChainByte::new();
+    byte.push(0xC0) // opFile
+        .add(encode(LC0(7))?) // CLOSE
+        .add(handle.var.clone());
+    Ok(byte.bytes)
+}
+
+/// Remove file or empty folder
+pub fn file_remove(path: &str) -> Result<Vec<u8>, ValError> {
+    let mut byte = ChainByte::new();
+    byte.push(0xC0) // opFile
+        .add(encode(LC0(30))?) // REMOVE
+        .add(encode(LCS(path))?);
+    Ok(byte.bytes)
+}
+
+/// Create folder. Reply 1 on success
+/// Return bytecodes and vector of `DataType`
+pub fn make_folder(cmd: &mut Command, path: &str) -> Result<(Vec<u8>, Vec<DataType>), ValError> {
+    let mut byte = ChainByte::new();
+    byte.push(0xC0) // opFile
+        .add(encode(LC0(10))?) // MAKE_FOLDER
+        .add(encode(LCS(path))?)
+        .add(cmd.allocate(DataType::DATA8, true)?);
+    Ok((byte.bytes, vec![DataType::DATA8]))
+}
+
+/// Count sub-folders in folder
+/// Return bytecodes and vector of `DataType`
+pub fn folder_count(cmd: &mut Command, path: &str) -> Result<(Vec<u8>, Vec<DataType>), ValError> {
+    let mut byte = ChainByte::new();
+    byte.push(0xC0) // opFile
+        .add(encode(LC0(13))?) // GET_FOLDERS
+        .add(encode(LCS(path))?)
+        .add(cmd.allocate(DataType::DATA8, true)?);
+    Ok((byte.bytes, vec![DataType::DATA8]))
+}
+
+/// Get name of sub-folder. `item` start at 1, `length` include zero terminator
+/// Return bytecodes and vector of `DataType`
+/// # Example
+/// List first 3 folders of projects
+/// ```
+/// use ev3_dc::Command;
+/// use ev3_dc::funcs::subfolder_name;
+///
+/// let mut cmd = Command::new();
+/// for item in 1..=3 {
+///     let (byte, _) = subfolder_name(&mut cmd, "../prjs", item, 32).unwrap();
+///     cmd.bytecode.extend(byte);
+/// }
+/// assert_eq!(cmd.reserved_bytes(), 96);
+/// ```
+pub fn subfolder_name(cmd: &mut Command, path: &str, item: u8, length: u8) -> Result<(Vec<u8>, Vec<DataType>), ValError> {
+    if item == 0 { return Err(ValError::InvalidRange(0, 1, u8::MAX as i32)) }
+    if length == 0 { return Err(ValError::InvalidRange(0, 1, u8::MAX as i32)) }
+    let dtype = DataType::DATAS(length as usize - 1);
+    let mut byte = ChainByte::new();
+    byte.push(0xC0) // opFile
+        .add(encode(LC0(15))?) // GET_SUBFOLDER_NAME
+        .add(encode(LCS(path))?)
+        .add(auto_const(item as i32)?)
+        .add(auto_const(length as i32)?)
+        .add(cmd.allocate(dtype, true)?);
+    Ok((byte.bytes, vec![dtype]))
+}
+
+/// Get name of current datalog file. `length` include zero terminator
+/// Return bytecodes and vector of `DataType`
+pub fn log_name(cmd: &mut Command, length: u8) -> Result<(Vec<u8>, Vec<DataType>), ValError> {
+    if length == 0 { return Err(ValError::InvalidRange(0, 1, u8::MAX as i32)) }
+    let dtype = DataType::DATAS(length as usize - 1);
+    let mut byte = ChainByte::new();
+    byte.push(0xC0) // opFile
+        .add(encode(LC0(25))?) // GET_LOG_NAME
+        .add(auto_const(length as i32)?)
+        .add(cmd.allocate(dtype, true)?);
+    Ok((byte.bytes, vec![dtype]))
+}
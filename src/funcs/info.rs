@@ -0,0 +1,117 @@
+//! Brick identity & health
+
+use alloc::{ string::{ String, ToString }, vec, vec::Vec };
+use crate::{ encode, Command, DataType, Encoding::*, ValError, Variable };
+use crate::error::ReplyError;
+use crate::utils::ChainByte;
+
+/// Brick identity & health from [`brick_info`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BrickInfo {
+    /// Brick name
+    pub name: String,
+    /// Firmware version (e.g. `V1.09H`)
+    pub firmware: String,
+    /// Hardware version
+    pub hardware: String,
+    /// OS version
+    pub os: String,
+    /// Firmware build
+    pub fw_build: String,
+    /// OS build
+    pub os_build: String,
+    /// Bluetooth MAC address
+    pub bt_mac: String,
+    /// Total user memory in KB
+    pub mem_total: u32,
+    /// Free user memory in KB
+    pub mem_free: u32,
+    /// Battery voltage in V
+    pub voltage: f32,
+    /// Battery current in A
+    pub current: f32,
+    /// Battery level in percent
+    pub battery: u8,
+}
+
+/// Variables of [`brick_info`] query. Parse reply memory with [`BrickInfoQuery::parse`]
+pub struct BrickInfoQuery {
+    vars: Vec<Variable>,
+}
+
+impl BrickInfoQuery {
+    /// Parse reply memory
+    pub fn parse(&self, memory: &[u8]) -> Result<BrickInfo, ReplyError> {
+        let need = self.vars.iter().map(|v| v.offset as usize + v.dtype.size()).max().unwrap_or(0);
+        if memory.len() < need { return Err(ReplyError::Truncated(memory.len(), need)) }
+        let raw = |i: usize| self.vars[i].read(memory).unwrap_or(&[]);
+        let text = |i: usize| String::from_utf8_lossy(raw(i).split(|b| *b == 0).next().unwrap_or(&[])).to_string();
+        let word = |i: usize| u32::from_le_bytes(raw(i).try_into().unwrap_or([0; 4]));
+        Ok(BrickInfo {
+            name: text(0),
+            firmware: text(1),
+            hardware: text(2),
+            os: text(3),
+            fw_build: text(4),
+            os_build: text(5),
+            bt_mac: text(6),
+            mem_total: word(7),
+            mem_free: word(8),
+            voltage: f32::from_bits(word(9)),
+            current: f32::from_bits(word(10)),
+            battery: raw(11).first().copied().unwrap_or(0)
+        })
+    }
+}
+
+/// Query brick name, versions, Bluetooth MAC address, memory usage & battery in one command
+/// # Example
+/// ```
+/// use ev3_dc::Command;
+/// use ev3_dc::funcs::brick_info;
+///
+/// let mut cmd = Command::new();
+/// let (byte, query) = brick_info(&mut cmd).unwrap();
+/// cmd.bytecode = byte;
+/// println!("SENT: {:02X?}", cmd.gen_bytes());
+/// let memory = vec![0_u8; cmd.reserved_bytes()]; // Reply memory
+/// let info = query.parse(&memory).unwrap();
+/// println!("{}: {} {}%", info.name, info.firmware, info.battery);
+/// ```
+pub fn brick_info(cmd: &mut Command) -> Result<(Vec<u8>, BrickInfoQuery), ValError> {
+    let mut byte = ChainByte::new();
+    let mut vars = vec![];
+    let mut alloc = |cmd: &mut Command, dtype: DataType| -> Result<Vec<u8>, ValError> {
+        let addr = cmd.allocate(dtype, true)?;
+        vars.push(*cmd.variables().last().unwrap());
+        Ok(addr)
+    };
+    byte.push(0xD3) // opCom_Get
+        .add(encode(LC0(13))?) // GET_BRICKNAME
+        .add(encode(LC0(13))?)
+        .add(alloc(cmd, DataType::DATAS(12))?);
+    for (sub, length) in [(10, 8), (9, 8), (3, 20), (11, 12), (12, 12)] { // GET_FW_VERS, GET_HW_VERS, GET_OS_VERS, GET_FW_BUILD, GET_OS_BUILD
+        byte.push(0x81) // opUI_Read
+            .add(encode(LC0(sub))?)
+            .add(encode(LC0(length))?)
+            .add(alloc(cmd, DataType::DATAS(length as usize - 1))?);
+    }
+    byte.push(0xD3) // opCom_Get
+        .add(encode(LC0(12))?) // GET_ID
+        .add(encode(LC0(2))?) // Bluetooth
+        .add(encode(LC0(13))?)
+        .add(alloc(cmd, DataType::DATAS(12))?)
+        .push(0xC5) // opMemory_Usage
+        .add(alloc(cmd, DataType::DATA32)?)
+        .add(alloc(cmd, DataType::DATA32)?);
+    for sub in [1, 2] { // GET_VBATT, GET_IBATT
+        byte.push(0x81) // opUI_Read
+            .add(encode(LC0(sub))?)
+            .add(alloc(cmd, DataType::DATAF)?);
+    }
+    byte.push(0x81) // opUI_Read
+        .add(encode(LC0(18))?) // GET_LBATT
+        .add(alloc(cmd, DataType::DATA8)?);
+    Ok((byte.bytes, BrickInfoQuery { vars }))
+}
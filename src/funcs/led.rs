@@ -0,0 +1,193 @@
+//! Brick LED
+
+use alloc::{ vec, vec::Vec };
+use core::time::Duration;
+use crate::{ encode, Command, Encoding::*, ValError };
+use crate::utils::ChainByte;
+use super::{ LedColor, LedEffect, Sequence };
+
+/// LED pattern as defined by firmware
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LedPattern {
+    Off,
+    Green,
+    Red,
+    Orange,
+    GreenFlash,
+    RedFlash,
+    OrangeFlash,
+    GreenPulse,
+    RedPulse,
+    OrangePulse,
+}
+
+impl LedPattern {
+    /// Every pattern in firmware order
+    pub const ALL: [LedPattern; 10] = [
+        LedPattern::Off, LedPattern::Green, LedPattern::Red, LedPattern::Orange,
+        LedPattern::GreenFlash, LedPattern::RedFlash, LedPattern::OrangeFlash,
+        LedPattern::GreenPulse, LedPattern::RedPulse, LedPattern::OrangePulse
+    ];
+    /// Get pattern from color & effect. `Off` ignore effect
+    pub fn new(color: LedColor, effect: LedEffect) -> Self {
+        let base = match color {
+            LedColor::Off => return LedPattern::Off,
+            LedColor::Green => 1,
+            LedColor::Red => 2,
+            LedColor::Orange => 3
+        };
+        let shift = match effect {
+            LedEffect::Static => 0,
+            LedEffect::Blink => 3,
+            LedEffect::Pulse => 6
+        };
+        LedPattern::ALL[base + shift]
+    }
+    /// Get pattern from firmware code
+    pub fn from_code(code: u8) -> Option<Self> {
+        LedPattern::ALL.get(code as usize).copied()
+    }
+    /// Get firmware code
+    pub fn code(&self) -> u8 {
+        LedPattern::ALL.iter().position(|p| p == self).unwrap_or(0) as u8
+    }
+    /// Get color
+    pub fn color(&self) -> LedColor {
+        match self.code() {
+            0 => LedColor::Off,
+            c if c % 3 == 1 => LedColor::Green,
+            c if c % 3 == 2 => LedColor::Red,
+            _ => LedColor::Orange
+        }
+    }
+    /// Get effect
+    pub fn effect(&self) -> LedEffect {
+        match self.code() {
+            0..=3 => LedEffect::Static,
+            4..=6 => LedEffect::Blink,
+            _ => LedEffect::Pulse
+        }
+    }
+    /// Get opUI_Write LED bytecode
+    /// # Example
+    /// ```
+    /// use ev3_dc::funcs::LedPattern;
+    ///
+    /// for (i, pattern) in LedPattern::ALL.iter().enumerate() {
+    ///     assert_eq!(LedPattern::from_code(i as u8), Some(*pattern));
+    ///     assert_eq!(LedPattern::new(pattern.color(), pattern.effect()), *pattern);
+    ///     assert_eq!(pattern.bytecode(), vec![0x82, 0x1B, i as u8]);
+    /// }
+    /// assert_eq!(LedPattern::from_code(10), None);
+    /// ```
+    pub fn bytecode(&self) -> Vec<u8> {
+        let mut byte = ChainByte::new();
+        byte.add(vec![0x82, 0x1B]) // opUI_Write LED
+            .add(encode(LC0(self.code() as i8)).unwrap());
+        byte.bytes
+    }
+}
+
+/// LED state tracker. Firmware has no LED read, so current pattern is remembered on host
+/// # Example
+/// ```
+/// use ev3_dc::funcs::{ Led, LedPattern };
+///
+/// let mut led = Led::new();
+/// assert_eq!(led.current(), LedPattern::Green);
+/// let byte = led.set(LedPattern::RedPulse);
+/// assert_eq!(byte, vec![0x82, 0x1B, 0x08]);
+/// assert_eq!(led.current(), LedPattern::RedPulse);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Led {
+    current: LedPattern,
+}
+
+impl Default for Led {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Led {
+    /// Create tracker with firmware's boot pattern (static green)
+    pub fn new() -> Self {
+        Led { current: LedPattern::Green }
+    }
+    /// Create tracker with known pattern
+    pub fn with(pattern: LedPattern) -> Self {
+        Led { current: pattern }
+    }
+    /// Set pattern and return bytecode
+    pub fn set(&mut self, pattern: LedPattern) -> Vec<u8> {
+        self.current = pattern;
+        pattern.bytecode()
+    }
+    /// Get last written pattern
+    pub fn current(&self) -> LedPattern {
+        self.current
+    }
+    /// Apply final pattern of timeline
+    pub fn apply(&mut self, timeline: &LedTimeline) {
+        if let Some((pattern, _)) = timeline.steps.last() {
+            self.current = *pattern;
+        }
+    }
+}
+
+/// Sequence of LED patterns with hold time. Compile into one direct command timed on brick
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LedTimeline {
+    steps: Vec<(LedPattern, Duration)>,
+}
+
+impl LedTimeline {
+    /// Create empty timeline
+    pub fn new() -> Self {
+        LedTimeline { steps: vec![] }
+    }
+    /// Show pattern and hold for duration. Zero duration doesn't wait
+    pub fn then(&mut self, pattern: LedPattern, hold: Duration) -> &mut Self {
+        self.steps.push((pattern, hold));
+        self
+    }
+    /// Get steps
+    pub fn steps(&self) -> &[(LedPattern, Duration)] {
+        &self.steps
+    }
+    /// Get total hold time
+    pub fn duration(&self) -> Duration {
+        self.steps.iter().map(|(_, hold)| *hold).sum()
+    }
+    /// Compile timeline. Allocates 1 local DATA32 as timer
+    /// # Example
+    /// Blink red for 2s then green
+    /// ```
+    /// use std::time::Duration;
+    /// use ev3_dc::Command;
+    /// use ev3_dc::funcs::{ LedPattern, LedTimeline };
+    ///
+    /// let mut cmd = Command::new();
+    /// let byte = LedTimeline::new()
+    ///     .then(LedPattern::RedFlash, Duration::from_secs(2))
+    ///     .then(LedPattern::Green, Duration::ZERO)
+    ///     .compile(&mut cmd).unwrap();
+    /// assert_eq!(byte, vec![
+    ///     0x82, 0x1B, 0x05,
+    ///     0x85, 0x82, 0xD0, 0x07, 0x40, // opTimer_Wait 2000 LV0(0)
+    ///     0x86, 0x40, // opTimer_Ready LV0(0)
+    ///     0x82, 0x1B, 0x01
+    /// ]);
+    /// assert_eq!(cmd.local_bytes(), 4);
+    /// ```
+    pub fn compile(&self, cmd: &mut Command) -> Result<Vec<u8>, ValError> {
+        let mut seq = Sequence::new();
+        for (pattern, hold) in &self.steps {
+            seq.then(pattern.bytecode())
+                .wait(*hold);
+        }
+        seq.compile(cmd)
+    }
+}
@@ -3,10 +3,28 @@
 //! Any functions that return value, will return (`Vec<u8>`, `Vec<`[`DataType`]`>`).
 //! Like TLV
 
+use alloc::{ vec, vec::Vec };
 use crate::{ encode, Command, DataType, Encoding::*, ValError };
 use crate::utils::ChainByte;
 
+mod program;
+mod info;
+mod config;
+mod button;
+mod led;
+mod timer;
+mod file;
+pub use program::*;
+pub use info::*;
+pub use config::*;
+pub use button::*;
+pub use led::*;
+pub use timer::*;
+pub use file::*;
+
 /// LED Color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LedColor {
    Red,
    Orange,
@@ -15,6 +33,8 @@ pub enum LedColor {
 }
 
 /// LED Animation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LedEffect {
     Static,
     Blink,
@@ -60,26 +80,14 @@ pub fn battery_percentage(cmd: &mut Command) -> Result<(Vec<u8>, Vec<DataType>),
     Ok((byte.bytes, vec![DataType::DATA8]))
 }
 
-/// Show LED
+/// Show LED. `Off` ignore effect
+/// # Example
+/// ```
+/// use ev3_dc::funcs::{ show_led, LedColor, LedEffect };
+///
+/// assert_eq!(show_led(LedColor::Green, LedEffect::Blink), vec![0x82, 0x1B, 0x04]);
+/// assert_eq!(show_led(LedColor::Off, LedEffect::Pulse), vec![0x82, 0x1B, 0x00]);
+/// ```
 pub fn show_led(color: LedColor, effect: LedEffect) -> Vec<u8> {
-    let mut byte = ChainByte::new();
-    byte.add(vec![0x82, 0x1B]);
-    if let LedColor::Off = color {
-        byte.add(encode(LC0(0)).unwrap());
-    }
-    let mut code: i8 = 0;
-    code += match color {
-        LedColor::Green => 1,
-        LedColor::Red => 2,
-        LedColor::Orange => 3,
-        _ => 0
-    };
-    code += match effect {
-        LedEffect::Static => 0,
-        LedEffect::Blink => 3,
-        LedEffect::Pulse => 6
-    };
-    byte.add(encode(LC0(code)).unwrap());
-    byte.bytes
+    LedPattern::new(color, effect).bytecode()
 }
-
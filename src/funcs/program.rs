@@ -0,0 +1,137 @@
+//! Program execution control
+
+use alloc::{ vec, vec::Vec };
+use crate::{ encode, Command, DataType, Encoding::*, ValError };
+use crate::utils::ChainByte;
+
+/// Program slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Slot {
+    /// Brick menu (GUI_SLOT)
+    Gui,
+    /// User program (USER_SLOT)
+    User,
+    /// Direct command (CMD_SLOT)
+    Cmd,
+    /// Terminal (TERM_SLOT)
+    Term,
+    /// Debug (DEBUG_SLOT)
+    Debug,
+}
+
+impl Slot {
+    /// Every slot in id order
+    pub const ALL: [Slot; 5] = [Slot::Gui, Slot::User, Slot::Cmd, Slot::Term, Slot::Debug];
+    /// Get program id (PRGID) of slot
+    pub fn id(&self) -> i8 {
+        match self {
+            Slot::Gui => 0,
+            Slot::User => 1,
+            Slot::Cmd => 2,
+            Slot::Term => 3,
+            Slot::Debug => 4
+        }
+    }
+}
+
+/// Program status from [`program_status`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProgramStatus {
+    Running,
+    Waiting,
+    Stopped,
+    Halted,
+    /// Status not defined by firmware
+    Unknown(u8),
+}
+
+impl ProgramStatus {
+    /// Get status from reply byte
+    pub fn from_byte(byte: u8) -> Self {
+        match byte {
+            0x10 => ProgramStatus::Running,
+            0x20 => ProgramStatus::Waiting,
+            0x40 => ProgramStatus::Stopped,
+            0x80 => ProgramStatus::Halted,
+            _ => ProgramStatus::Unknown(byte)
+        }
+    }
+}
+
+/// Load image file into slot and start it. Allocates 2 local DATA32 for image size & address
+/// # Example
+/// Run uploaded program in user slot
+/// ```
+/// use ev3_dc::Command;
+/// use ev3_dc::funcs::{ start_program, Slot };
+///
+/// let mut cmd = Command::new();
+/// cmd.bytecode = start_program(&mut cmd, Slot::User, "../prjs/demo/demo.rbf", false).unwrap();
+/// println!("SENT: {:02X?}", cmd.gen_bytes());
+/// ```
+pub fn start_program(cmd: &mut Command, slot: Slot, path: &str, debug: bool) -> Result<Vec<u8>, ValError> {
+    let size = cmd.allocate(DataType::DATA32, false)?;
+    let address = cmd.allocate(DataType::DATA32, false)?;
+    let mut byte = ChainByte::new();
+    byte.push(0xC0) // opFile
+        .add(encode(LC0(8))?) // LOAD_IMAGE
+        .add(encode(LC0(slot.id()))?)
+        .add(encode(LCS(path))?)
+        .add(size.clone())
+        .add(address.clone())
+        .push(0x03) // opProgram_Start
+        .add(encode(LC0(slot.id()))?)
+        .add(size)
+        .add(address)
+        .add(encode(LC0(debug as i8))?);
+    Ok(byte.bytes)
+}
+
+/// Stop program in slot
+pub fn stop_program(slot: Slot) -> Result<Vec<u8>, ValError> {
+    let mut byte = ChainByte::new();
+    byte.push(0x02) // opProgram_Stop
+        .add(encode(LC0(slot.id()))?);
+    Ok(byte.bytes)
+}
+
+// opProgram_Info with single output
+fn program_info(cmd: &mut Command, slot: Slot, sub: i8, dtype: DataType) -> Result<(Vec<u8>, Vec<DataType>), ValError> {
+    let mut byte = ChainByte::new();
+    byte.push(0x0C) // opProgram_Info
+        .add(encode(LC0(sub))?)
+        .add(encode(LC0(slot.id()))?)
+        .add(cmd.allocate(dtype, true)?);
+    Ok((byte.bytes, vec![dtype]))
+}
+
+/// Get program status of slot. Parse with [`ProgramStatus::from_byte`]
+/// # Example
+/// Query status of every slot in one command
+/// ```
+/// use ev3_dc::Command;
+/// use ev3_dc::funcs::{ program_status, ProgramStatus, Slot };
+///
+/// let mut cmd = Command::new();
+/// for slot in Slot::ALL {
+///     let (byte, _) = program_status(&mut cmd, slot).unwrap();
+///     cmd.bytecode.extend(byte);
+/// }
+/// let memory = [0x10, 0x40, 0x10, 0x40, 0x40]; // Reply memory
+/// assert_eq!(ProgramStatus::from_byte(memory[Slot::User.id() as usize]), ProgramStatus::Stopped);
+/// ```
+pub fn program_status(cmd: &mut Command, slot: Slot) -> Result<(Vec<u8>, Vec<DataType>), ValError> {
+    program_info(cmd, slot, 22, DataType::DATA8)
+}
+
+/// Get program speed (instructions per second) of slot
+pub fn program_speed(cmd: &mut Command, slot: Slot) -> Result<(Vec<u8>, Vec<DataType>), ValError> {
+    program_info(cmd, slot, 23, DataType::DATA32)
+}
+
+/// Get program result of slot
+pub fn program_result(cmd: &mut Command, slot: Slot) -> Result<(Vec<u8>, Vec<DataType>), ValError> {
+    program_info(cmd, slot, 24, DataType::DATA8)
+}
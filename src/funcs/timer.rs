@@ -0,0 +1,147 @@
+//! On-brick timers & timed sequences
+
+use alloc::{ vec, vec::Vec };
+use core::time::Duration;
+use crate::{ Command, DataType, ValError };
+use crate::utils::{ ChainByte, auto_const };
+
+fn millis(time: Duration) -> Result<i32, ValError> {
+    i32::try_from(time.as_millis())
+        .map_err(|_| ValError::PosOverflow(time.as_millis().min(u32::MAX as u128) as u32, i32::MAX as u32))
+}
+
+/// Start timer. `timer` is DATA32 variable from [`Command::allocate`]
+/// # Example
+/// ```
+/// use ev3_dc::{ Command, DataType::* };
+/// use ev3_dc::funcs::{ timer_wait, timer_ready };
+///
+/// let mut cmd = Command::new();
+/// let timer = cmd.allocate(DATA32, false).unwrap();
+/// let mut byte = timer_wait(500, &timer).unwrap();
+/// byte.extend(timer_ready(&timer));
+/// assert_eq!(byte, vec![0x85, 0x82, 0xF4, 0x01, 0x40, 0x86, 0x40]);
+/// ```
+pub fn timer_wait(ms: i32, timer: &[u8]) -> Result<Vec<u8>, ValError> {
+    if ms < 0 { return Err(ValError::InvalidRange(ms, 0, i32::MAX)) }
+    let mut byte = ChainByte::new();
+    byte.push(0x85) // opTimer_Wait
+        .add(auto_const(ms)?)
+        .add(timer.to_vec());
+    Ok(byte.bytes)
+}
+
+/// Block until timer started by [`timer_wait`] elapse
+pub fn timer_ready(timer: &[u8]) -> Vec<u8> {
+    let mut byte = ChainByte::new();
+    byte.push(0x86) // opTimer_Ready
+        .add(timer.to_vec());
+    byte.bytes
+}
+
+/// Read free running timer in ms
+/// Return bytecodes and vector of `DataType`
+pub fn timer_read(cmd: &mut Command) -> Result<(Vec<u8>, Vec<DataType>), ValError> {
+    let mut byte = ChainByte::new();
+    byte.push(0x87) // opTimer_Read
+        .add(cmd.allocate(DataType::DATA32, true)?);
+    Ok((byte.bytes, vec![DataType::DATA32]))
+}
+
+/// Read free running timer in µs
+/// Return bytecodes and vector of `DataType`
+pub fn timer_read_us(cmd: &mut Command) -> Result<(Vec<u8>, Vec<DataType>), ValError> {
+    let mut byte = ChainByte::new();
+    byte.push(0x8F) // opTimer_Read_us
+        .add(cmd.allocate(DataType::DATA32, true)?);
+    Ok((byte.bytes, vec![DataType::DATA32]))
+}
+
+/// Step of [`Sequence`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Step {
+    /// Bytecode to run
+    Action(Vec<u8>),
+    /// Delay timed on brick
+    Wait(Duration),
+}
+
+/// Actions with delays. Compile into one direct command timed on brick
+/// # Example
+/// Motor on, wait 500 ms, beep, motor off
+/// ```
+/// use std::time::Duration;
+/// use ev3_dc::Command;
+/// use ev3_dc::funcs::{ motor_speed, stop_motor, Sequence };
+///
+/// let mut cmd = Command::new();
+/// let byte = Sequence::new()
+///     .then(motor_speed(1, 50, 0).unwrap())
+///     .wait(Duration::from_millis(500))
+///     .then(vec![0x94, 0x01, 0x81, 0x32, 0x82, 0xB8, 0x01, 0x81, 0x64]) // opSound TONE
+///     .wait(Duration::from_millis(100))
+///     .then(stop_motor(1, 0, false).unwrap())
+///     .compile(&mut cmd).unwrap();
+/// assert_eq!(cmd.local_bytes(), 4); // One timer shared by every wait
+/// println!("{:02X?}", byte);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sequence {
+    steps: Vec<Step>,
+}
+
+impl Sequence {
+    /// Create empty sequence
+    pub fn new() -> Self {
+        Sequence { steps: vec![] }
+    }
+    /// Append bytecode
+    pub fn then(&mut self, bytecode: Vec<u8>) -> &mut Self {
+        self.steps.push(Step::Action(bytecode));
+        self
+    }
+    /// Append delay. Zero delay is ignored
+    pub fn wait(&mut self, time: Duration) -> &mut Self {
+        if !time.is_zero() {
+            self.steps.push(Step::Wait(time));
+        }
+        self
+    }
+    /// Get steps
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+    /// Get total delay
+    pub fn duration(&self) -> Duration {
+        self.steps.iter().map(|step| match step {
+            Step::Wait(time) => *time,
+            Step::Action(_) => Duration::ZERO
+        }).sum()
+    }
+    /// Compile sequence. Allocates 1 local DATA32 as timer when sequence has delay
+    pub fn compile(&self, cmd: &mut Command) -> Result<Vec<u8>, ValError> {
+        let mut byte = ChainByte::new();
+        let mut timer: Option<Vec<u8>> = None;
+        for step in &self.steps {
+            match step {
+                Step::Action(code) => { byte.add(code.clone()); },
+                Step::Wait(time) => {
+                    let ms = millis(*time)?;
+                    let var = match &timer {
+                        Some(var) => var.clone(),
+                        None => {
+                            let var = cmd.allocate(DataType::DATA32, false)?;
+                            timer = Some(var.clone());
+                            var
+                        }
+                    };
+                    byte.add(timer_wait(ms, &var)?)
+                        .add(timer_ready(&var));
+                }
+            }
+        }
+        Ok(byte.bytes)
+    }
+}
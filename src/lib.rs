@@ -11,6 +11,26 @@
 //!    - Subcommands merging
 //!    - PBM image Run-Length-Encoding
 //!    - Various helper function inside [`funcs`]
+//!    - Arithmetic expression compiler in [`expr`]
+//!    - OpCode catalogue & disassembler in [`opcode`]
+//!    - Labels & jumps in [`utils::ChainByte`]
+//!    - LMS2012 assembler in [`asm`]
+//!    - RBF executable image in [`rbf`]
+//!    - System command (file transfer) in [`system`]
+//!    - Mailbox messaging in [`mailbox`]
+//!    - Transport & client in [`transport`]
+//!    - Brick-side datalog in [`datalog`]
+//!    - Periodic batched sampler in [`sampler`]
+//!    - Packet planner in [`planner`]
+//!    - Interactive shell in [`repl`]
+//!
+//! ### Cargo features:
+//!    - `std` (default): [`transport`], [`sampler`] & [`repl`]. Without it, crate is `no_std` and only needs `alloc`
+//!    - `usb`: USB transport
+//!    - `cli`: `ev3dc` command-line tool
+//!    - `serde`: Serialize & Deserialize for [`Command`] (including allocation), [`parser::Reply`], [`DataType`], [`Encoding`],
+//!      [`utils::Span`] and typed values. Field & variant names are the Rust names and kept stable.
+//!      Deserialized [`Command`] is checked against [`GLOBAL_MAX`] / [`LOCAL_MAX`] and its variables
 //! 
 //! More information about Direct commands and list of all bytecodes are available at
 //! [MINDSTORMS Firmware Developer Kit](https://assets.education.lego.com/v3/assets/blt293eea581807678a/blt09ac3101d9df2051/5f88037a69efd81ab4debf2e/lego-mindstorms-ev3-communication-developer-kit.pdf?locale=en-us)
@@ -20,35 +40,110 @@
 //! use ev3_dc::{ Command, Encoding::*, encode };
 //!
 //! let mut cmd = Command::new();
-//! let mut byte = vec![0x84, 0x1B]; // OpUI_Write, LED
+//! let mut byte = vec![0x82, 0x1B]; // OpUI_Write, LED
 //! byte.extend(encode(LC0(0x04)).unwrap()); // Green flashing
 //! cmd.bytecode = byte;
 //! println!("SENT: {:02X?}", cmd.gen_bytes());
 //! // and send actual bytes via HID, or Bluetooth, etc.
 //! ```
+//! ## Store command as JSON (`serde` feature)
+//! ```
+//! # #[cfg(feature = "serde")] {
+//! use ev3_dc::{ Command, DataType::* };
+//!
+//! let mut cmd = Command::new();
+//! let var = cmd.allocate(DATA8, true).unwrap();
+//! cmd.bytecode = [vec![0x81, 0x12], var].concat(); // opUI_Read GET_LBATT
+//! let json = serde_json::to_string(&cmd).unwrap();
+//! assert_eq!(json, r#"{"id":170,"reply":true,"global":1,"local":0,"variables":[{"scope":"Global","offset":0,"dtype":"DATA8"}],"bytecode":[129,18,96]}"#);
+//! let copy: Command = serde_json::from_str(&json).unwrap();
+//! assert_eq!(copy.gen_bytes(), cmd.gen_bytes());
+//! assert_eq!(copy.reserved_bytes(), 1);
+//! // Memory beyond limits, or variable outside of reserved memory, is rejected
+//! assert!(serde_json::from_str::<Command>(&json.replace(r#""global":1"#, r#""global":5000"#)).is_err());
+//! assert!(serde_json::from_str::<Command>(&json.replace(r#""global":1"#, r#""global":0"#)).is_err());
+//! # }
+//! ```
+
+#![no_std]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
-use std::error::Error;
+use alloc::{ format, string::{ String, ToString }, vec, vec::Vec };
 use displaystr::display;
 pub mod utils;
 pub mod parser;
 pub mod funcs;
+pub mod error;
+pub mod expr;
+pub mod opcode;
+pub mod asm;
+pub mod rbf;
+pub mod system;
+pub mod mailbox;
+#[cfg(feature = "std")]
+pub mod transport;
+pub mod datalog;
+#[cfg(feature = "std")]
+pub mod sampler;
+pub mod planner;
+#[cfg(feature = "std")]
+pub mod repl;
+
+pub use error::{ Error, ValError };
 
 /// EV3 DataType.
 ///
 /// DATAN is for custom array
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[display]
 pub enum DataType {
     /// 8-bits value
-    DATA8,
+    DATA8 = "DATA8",
     /// 16-bits value
-    DATA16,
+    DATA16 = "DATA16",
     /// 32-bits value
-    DATA32,
+    DATA32 = "DATA32",
     /// IEEE-754 single precision float i.e. [`f32`]
-    DATAF,
+    DATAF = "DATAF",
     /// Array
-    DATAN(usize), // Custom length allocation
+    DATAN(usize) = "DATAN({_0})", // Custom length allocation
     /// Zero-terminated string
-    DATAS(usize)
+    DATAS(usize) = "DATAS({_0})"
+}
+
+impl DataType {
+    /// Size in memory. [`DataType::DATAS`] includes zero terminator
+    pub fn size(&self) -> usize {
+        match self {
+            DataType::DATA8 => 1,
+            DataType::DATA16 => 2,
+            DataType::DATA32 | DataType::DATAF => 4,
+            DataType::DATAN(length) => *length,
+            DataType::DATAS(length) => length.saturating_add(1)
+        }
+    }
+    /// Memory alignment expected by VM
+    pub fn align(&self) -> usize {
+        match self {
+            DataType::DATA16 => 2,
+            DataType::DATA32 | DataType::DATAF => 4,
+            _ => 1
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[display]
+/// Memory space of a variable
+pub enum MemScope {
+    /// Local memory. Used as scratch space, not returned in reply
+    Local = "local",
+    /// Global memory. Returned in reply
+    Global = "global"
 }
 
 /// Parameter encoding
@@ -56,54 +151,168 @@ pub enum DataType {
 /// LCx: Local constant value
 /// LVx: Local variable address
 /// GVx: Global variable address
-/// GV4 & LV4 & LV2 are unusable in direct command
+/// HLVx / HGVx: Handle stored in local / global variable (`@` in LMS assembler)
+/// LV2, LV4 & GV4 address beyond direct command memory, but are valid in programs
 /// 
 /// There is [`utils::auto_const`] for automatic integer encoding
+/// # Example
+/// ```
+/// # #[cfg(feature = "serde")] {
+/// use ev3_dc::Encoding::{ self, * };
+///
+/// for enc in [LC1(-5), LCF(1.5), GV2(300), LCS("hi")] {
+///     let json = serde_json::to_string(&enc).unwrap();
+///     assert_eq!(serde_json::from_str::<Encoding>(&json).unwrap(), enc);
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Encoding<'a> {
-    /// 5-bits constant integer (-32 - 31)
+    /// 6-bits constant integer (-32 - 31)
     LC0(i8),
-    /// 7-bits constant integer (-127 - 127)
+    /// 8-bits constant integer (-128 - 127)
     LC1(i8),
-    /// 15-bits constant integer (-32,767 - 32,767)
+    /// 16-bits constant integer (-32,768 - 32,767)
     LC2(i16),
-    /// 31-bits constant integer (-2,147,483,647 - 2,147,483,647)
+    /// 32-bits constant integer (-2,147,483,648 - 2,147,483,647)
     LC4(i32),
     /// IEEE-754 single precision floating point constant
     LCF(f32),
-    /// 5-bits local address
+    /// 5-bits local address (0 - 31)
     LV0(u8),
-    /// 7-bits local address
+    /// 8-bits local address
     LV1(u8),
-    /// 5-bits global address (-32 - 31)
+    /// 16-bits local address
+    LV2(u16),
+    /// 32-bits local address
+    LV4(u32),
+    /// 5-bits global address (0 - 31)
     GV0(u8),
-    /// 7-bits global address (-127 - 127)
+    /// 8-bits global address
     GV1(u8),
-    /// 15-bits global address (-32,767 - 32,767)
+    /// 16-bits global address
     GV2(u16),
+    /// 32-bits global address
+    GV4(u32),
+    /// Handle at 8-bits local address
+    HLV1(u8),
+    /// Handle at 16-bits local address
+    HLV2(u16),
+    /// Handle at 32-bits local address
+    HLV4(u32),
+    /// Handle at 8-bits global address
+    HGV1(u8),
+    /// Handle at 16-bits global address
+    HGV2(u16),
+    /// Handle at 32-bits global address
+    HGV4(u32),
     /// String (auto zero-terminated)
     LCS(&'a str),
 }
 
+impl Encoding<'_> {
+    /// Get integer value of constant. Return [`None`] for variable, float or string
+    pub fn as_int(&self) -> Option<i32> {
+        match self {
+            Encoding::LC0(val) | Encoding::LC1(val) => Some(*val as i32),
+            Encoding::LC2(val) => Some(*val as i32),
+            Encoding::LC4(val) => Some(*val),
+            _ => None
+        }
+    }
+}
+
 /// Direct Command object.
 /// [`Command::bytecode`] can contains multiple OpCodes
 /// # Example
 /// ```
+/// use ev3_dc::Command;
+///
 /// let mut cmd = Command::new();
 /// let mut byte = vec![];
 /// // Add bytecode to byte
 /// cmd.bytecode = byte;
 /// println!("SENT: {:02X?}", cmd.gen_bytes());
 /// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "CommandData"))]
 pub struct Command {
     /// Command ID
     pub id: u16,
     /// Reply to direct command
     pub reply: bool,
-    allocation: u16,
+    global: u16,
+    local: u16,
+    variables: Vec<Variable>,
     /// Bytes containing OpCodes and Parameter 
     pub bytecode: Vec<u8>,
 }
 
+// Unchecked Command for deserializing
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct CommandData {
+    id: u16,
+    reply: bool,
+    global: u16,
+    local: u16,
+    variables: Vec<Variable>,
+    bytecode: Vec<u8>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<CommandData> for Command {
+    type Error = ValError;
+    fn try_from(data: CommandData) -> Result<Self, ValError> {
+        for (used, max) in [(data.global, GLOBAL_MAX), (data.local, LOCAL_MAX)] {
+            if used > max { return Err(ValError::InvalidRange(used as i32, 0, max as i32)) }
+        }
+        for var in &data.variables {
+            let (used, max) = match var.scope {
+                MemScope::Global => (data.global, GLOBAL_MAX),
+                MemScope::Local => (data.local, LOCAL_MAX)
+            };
+            let size = var.dtype.size();
+            if (var.offset as usize).checked_add(size).is_none_or(|end| end > used as usize) {
+                return Err(ValError::MemOverflow(size, used, max, var.scope))
+            }
+        }
+        let CommandData { id, reply, global, local, variables, bytecode } = data;
+        Ok(Command { id, reply, global, local, variables, bytecode })
+    }
+}
+
+/// Variable allocated by [`Command::allocate`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Variable {
+    /// Memory space
+    pub scope: MemScope,
+    /// Byte offset in memory space
+    pub offset: u16,
+    /// Variable type
+    pub dtype: DataType,
+}
+
+impl Variable {
+    /// Encode variable address as parameter
+    pub fn encode(&self) -> Result<Vec<u8>, ValError> {
+        match (self.scope, self.offset) {
+            (MemScope::Global, 0..32) => encode(Encoding::GV0(self.offset as u8)),
+            (MemScope::Global, 32..256) => encode(Encoding::GV1(self.offset as u8)),
+            (MemScope::Global, _) => encode(Encoding::GV2(self.offset)),
+            (MemScope::Local, 0..32) => encode(Encoding::LV0(self.offset as u8)),
+            (MemScope::Local, 32..256) => encode(Encoding::LV1(self.offset as u8)),
+            (MemScope::Local, _) => encode(Encoding::LV2(self.offset))
+        }
+    }
+    /// Get variable bytes from reply memory. Return [`None`] for local variable or short memory
+    pub fn read<'a>(&self, memory: &'a [u8]) -> Option<&'a [u8]> {
+        if self.scope == MemScope::Local { return None }
+        memory.get((self.offset as usize)..(self.offset as usize + self.dtype.size()))
+    }
+}
+
 // Constants
 /// USB VendorId of EV3
 pub const VID: u16 = 0x0694;
@@ -122,26 +331,10 @@ pub struct Port {
 }
 /// PORT Constants. Add them together to use multiple ports
 pub const PORT: Port = Port { A: 1, B: 2, C: 4, D: 8, ALL: 15};
-
-#[derive(Debug)]
-#[display]
-/// ev3_dc Error type
-pub enum ValError {
-    // Error for encoding
-    /// [`encode`] failed to encode overflowed value
-    PosOverflow(u32, u32) = "Encode Error: Value {_0} overflowed {_1}",
-    /// [`encode`] failed to encode underflowed value
-    NegOverflow(i32, i32) = "Encode Error: Value {_0} overflowed {_1}",
-    // Error for functions
-    /// [`Command::allocate`] failed to allocated variable in memory
-    MemOverflow(u16, u16, u16, String) = "Allocation Error: Cannot allocate data with size {_0}. Allocated {_3} byte(s). Memory: {_1}/{_2}",
-    /// Value isn't in valid range
-    InvalidRange(i32, i32, i32) = "Invalid Range: Expect {_1} - {_2} got {_0}",
-    /// Value isn't valid
-    InvalidValue(i32, i32) = "Invalid Value: Expect {_1} got {_0}"
-}
-
-impl Error for ValError {}
+/// Maximum global memory of direct command
+pub const GLOBAL_MAX: u16 = 1019;
+/// Maximum local memory of direct command
+pub const LOCAL_MAX: u16 = 63;
 
 impl Command {
     pub fn new() -> Self { Command::default() }
@@ -153,23 +346,33 @@ impl Command {
             true => 0x00,
             false => 0x80,
         });
-        packet.extend(self.allocation.to_le_bytes());
+        packet.extend(((self.local << 10) + self.global).to_le_bytes());
         packet.extend(&self.bytecode);
         let ln = ((5 + self.bytecode.len()) as u16).to_le_bytes();
         packet[0] = ln[0];
         packet[1] = ln[1];
         packet
     }
-    /// Get reserved bytes
+    /// Get reserved global bytes, including alignment padding.
     /// Can be use to initialize response buffer, with length of `5 + reserved_bytes()`.
     pub fn reserved_bytes(&self) -> usize {
-        ((self.allocation >> 10) + (self.allocation & ((1 << 10) - 1))) as usize
+        self.global as usize
+    }
+    /// Get reserved local bytes, including alignment padding
+    pub fn local_bytes(&self) -> usize {
+        self.local as usize
+    }
+    /// Get every variable allocated by [`Command::allocate`] in allocation order
+    pub fn variables(&self) -> &[Variable] {
+        &self.variables
     }
     /// Deallocate bytes from [`Command::allocate`].
     /// Use this when you want to use same [`Command`]. \
     /// **Causing any variables in bytecode to not work**
     pub fn mem_free(&mut self) {
-        self.allocation = 0;
+        self.global = 0;
+        self.local = 0;
+        self.variables.clear();
     }
 }
 
@@ -179,105 +382,193 @@ impl Command {
 /// # Example
 /// Encode local constant as [`Encoding::LC1`] with value of 42
 /// ```
+/// use ev3_dc::{ encode, Encoding::* };
+///
 /// let byte: Vec<u8> = encode(LC1(42)).unwrap();
-/// println("Bytecode: {:02X?}", byte);
+/// println!("Bytecode: {:02X?}", byte);
 /// ```
 pub fn encode(encoding: Encoding) -> Result<Vec<u8>, ValError> {
-let mut bytes: Vec<u8> = vec![];
-    let mut head: u8 = 0;
+    let mut bytes: Vec<u8> = vec![];
     match encoding {
         Encoding::LC0(val) => {
             if val > 31 { return Err(ValError::PosOverflow(val as u32, 31)) }
-            if val < -31 { return Err(ValError::NegOverflow(val as i32, -31)) }
-            if val < 0 { head += 1 << 5;}
-            head += (val.abs() & 0b11111) as u8
+            if val < -32 { return Err(ValError::NegOverflow(val as i32, -32)) }
+            bytes.push(val as u8 & 0b111111);
         }
-        Encoding::GV0(val) | Encoding::LV0(val) => {
+        Encoding::LV0(val) | Encoding::GV0(val) => {
             if val > 31 { return Err(ValError::PosOverflow(val as u32, 31)) }
-            head += 1 << 6;
-            if let Encoding::GV0(_) = encoding { head += 1 << 5; }
-            head += val & 0b11111;
-        }
-        Encoding::LCS(_) => {
-            head += 0b10000100;
-        }
-        _ => { head += 1 << 7; }
-    }
-    bytes.push(head);
-    let res: Result<Vec<u8>, ValError> = match encoding {
-        Encoding::LC1(val) => { 
-            head += 1;
-            if val == i8::MIN { return Err(ValError::NegOverflow(val as i32, i8::MIN as i32)) }
-            if val < 0 { head += 1 << 5; }
-            Ok((val.abs() & i8::MAX).to_le_bytes().to_vec())
-        }
-        Encoding::LC2(val) => {
-            head += 2;
-            if val == i16::MIN { return Err(ValError::NegOverflow(val as i32, i16::MIN as i32)) }
-            if val < 0 { head += 1 << 5; }
-            Ok((val.abs() & i16::MAX).to_le_bytes().to_vec())
-        }
-        Encoding::LC4(val) => {
-            head += 3;
-            if val == i32::MIN { return Err(ValError::NegOverflow(val, i32::MIN)) }
-            if val < 0 { head += 1 << 5; }
-            Ok((val.abs() & i32::MAX).to_le_bytes().to_vec())
-        }
-        Encoding::LV1(val) | Encoding::GV1(val) => {
-            head += (1 << 5) + 1;
-            Ok(val.to_le_bytes().to_vec())
-        }
-        Encoding::GV2(val) => {
-            head += (1 << 5) + 2;
-            Ok(val.to_le_bytes().to_vec())
+            let scope = if let Encoding::GV0(_) = encoding { 1 << 5 } else { 0 };
+            bytes.push((1 << 6) + scope + val);
         }
+        Encoding::LC1(val) => bytes.extend([0x81, val as u8]),
+        Encoding::LC2(val) => { bytes.push(0x82); bytes.extend(val.to_le_bytes()); }
+        Encoding::LC4(val) => { bytes.push(0x83); bytes.extend(val.to_le_bytes()); }
+        Encoding::LCF(val) => { bytes.push(0x83); bytes.extend(val.to_le_bytes()); }
+        Encoding::LV1(val) => bytes.extend([0xC1, val]),
+        Encoding::LV2(val) => { bytes.push(0xC2); bytes.extend(val.to_le_bytes()); }
+        Encoding::LV4(val) => { bytes.push(0xC3); bytes.extend(val.to_le_bytes()); }
+        Encoding::GV1(val) => bytes.extend([0xE1, val]),
+        Encoding::GV2(val) => { bytes.push(0xE2); bytes.extend(val.to_le_bytes()); }
+        Encoding::GV4(val) => { bytes.push(0xE3); bytes.extend(val.to_le_bytes()); }
+        Encoding::HLV1(val) => bytes.extend([0xD1, val]),
+        Encoding::HLV2(val) => { bytes.push(0xD2); bytes.extend(val.to_le_bytes()); }
+        Encoding::HLV4(val) => { bytes.push(0xD3); bytes.extend(val.to_le_bytes()); }
+        Encoding::HGV1(val) => bytes.extend([0xF1, val]),
+        Encoding::HGV2(val) => { bytes.push(0xF2); bytes.extend(val.to_le_bytes()); }
+        Encoding::HGV4(val) => { bytes.push(0xF3); bytes.extend(val.to_le_bytes()); }
         Encoding::LCS(val) => {
-            let mut tmp = val.as_bytes().to_vec();
-            tmp.push(0);
-            Ok(tmp)
-        }
-        Encoding::LCF(val) => {
-            head += 3;
-            Ok(val.to_le_bytes().to_vec())
+            if val.contains('\0') { return Err(ValError::InvalidString(val.to_string())) }
+            bytes.push(0x84);
+            bytes.extend(val.as_bytes());
+            bytes.push(0);
         }
-        _ => Ok(vec![]),
-    };
-    bytes.extend(res?);
-    bytes[0] = head;
+    }
     Ok(bytes)
 }
 
+/// Decode first parameter from bytecode. Reverse of [`encode`]. \
+/// Return decoded parameter and number of bytes read.
+///
+/// 4-bytes constant is decoded as [`Encoding::LC4`], as [`Encoding::LCF`] shares same encoding.
+/// Zero-terminated string in both long formats (`0x80` & `0x84`) is decoded as [`Encoding::LCS`]
+/// # Example
+/// Round-trip every short constant and a few long parameters
+/// ```
+/// use ev3_dc::{ encode, decode, Encoding::* };
+///
+/// for val in -32..=31 {
+///     let byte = encode(LC0(val)).unwrap();
+///     assert_eq!(byte.len(), 1);
+///     assert_eq!(decode(&byte).unwrap(), (LC0(val), 1));
+/// }
+/// for val in i8::MIN..=i8::MAX {
+///     assert_eq!(decode(&encode(LC1(val)).unwrap()).unwrap(), (LC1(val), 2));
+/// }
+/// for val in (i16::MIN..=i16::MAX).step_by(7).chain([i16::MAX]) {
+///     assert_eq!(decode(&encode(LC2(val)).unwrap()).unwrap(), (LC2(val), 3));
+/// }
+/// for val in [i32::MIN, -65536, -1, 0, 1, 65536, i32::MAX] {
+///     assert_eq!(decode(&encode(LC4(val)).unwrap()).unwrap(), (LC4(val), 5));
+/// }
+/// for val in 0..=31 {
+///     assert_eq!(decode(&encode(LV0(val)).unwrap()).unwrap(), (LV0(val), 1));
+///     assert_eq!(decode(&encode(GV0(val)).unwrap()).unwrap(), (GV0(val), 1));
+/// }
+/// for enc in [LV1(200), LV2(1000), LV4(70000), GV1(255), GV2(1019), GV4(u32::MAX),
+///     HLV1(4), HLV2(300), HLV4(70000), HGV1(8), HGV2(600), HGV4(1 << 20), LCS(""), LCS("ev3")] {
+///         let byte = encode(enc).unwrap();
+///         assert_eq!(decode(&byte).unwrap(), (enc, byte.len()));
+/// }
+/// let byte = encode(LCF(1.5)).unwrap();
+/// assert_eq!(encode(decode(&byte).unwrap().0).unwrap(), byte);
+/// assert!(encode(LC0(-33)).is_err());
+/// assert!(encode(LCS("a\0b")).is_err());
+/// assert!(decode(&[0x82, 0x00]).is_err());
+/// ```
+pub fn decode(bytes: &[u8]) -> Result<(Encoding<'_>, usize), ValError> {
+    let head = *bytes.first().ok_or(ValError::Incomplete(0, 1))?;
+    if head & 0x80 == 0 {
+        let val = head & 0b11111;
+        return Ok((match head >> 5 {
+            0 => Encoding::LC0(val as i8),
+            1 => Encoding::LC0(val as i8 - 32),
+            2 => Encoding::LV0(val),
+            _ => Encoding::GV0(val)
+        }, 1));
+    }
+    if head == 0x80 || head == 0x84 {
+        let end = bytes[1..].iter().position(|b| *b == 0)
+            .ok_or(ValError::Incomplete(bytes.len(), bytes.len() + 1))?;
+        let text = str::from_utf8(&bytes[1..(1 + end)]).map_err(|_| ValError::Decode(head))?;
+        return Ok((Encoding::LCS(text), end + 2));
+    }
+    let size: usize = match head & 0b111 {
+        1 => 1,
+        2 => 2,
+        3 => 4,
+        _ => return Err(ValError::Decode(head))
+    };
+    if bytes.len() < 1 + size { return Err(ValError::Incomplete(bytes.len(), 1 + size)) }
+    let mut raw = [0_u8; 4];
+    raw[..size].copy_from_slice(&bytes[1..(1 + size)]);
+    let val = u32::from_le_bytes(raw);
+    let enc = match (head & 0b1111_1000, size) {
+        (0x80, 1) => Encoding::LC1(val as u8 as i8),
+        (0x80, 2) => Encoding::LC2(val as u16 as i16),
+        (0x80, _) => Encoding::LC4(val as i32),
+        (0xC0, 1) => Encoding::LV1(val as u8),
+        (0xC0, 2) => Encoding::LV2(val as u16),
+        (0xC0, _) => Encoding::LV4(val),
+        (0xE0, 1) => Encoding::GV1(val as u8),
+        (0xE0, 2) => Encoding::GV2(val as u16),
+        (0xE0, _) => Encoding::GV4(val),
+        (0xD0, 1) => Encoding::HLV1(val as u8),
+        (0xD0, 2) => Encoding::HLV2(val as u16),
+        (0xD0, _) => Encoding::HLV4(val),
+        (0xF0, 1) => Encoding::HGV1(val as u8),
+        (0xF0, 2) => Encoding::HGV2(val as u16),
+        (0xF0, _) => Encoding::HGV4(val),
+        _ => return Err(ValError::Decode(head))
+    };
+    Ok((enc, 1 + size))
+}
+
 impl Command {
     /// Create variable bytecode and allocate space in [`Command`]. 
-    /// Create global variable for results to be read in reply's bytes
+    /// Create global variable for results to be read in reply's bytes. \
+    /// DATA16 is aligned to 2 bytes, DATA32 & DATAF are aligned to 4 bytes.
+    /// # Example
+    /// ```
+    /// use ev3_dc::{ Command, DataType::* };
+    ///
+    /// let mut cmd = Command::new();
+    /// assert_eq!(cmd.allocate(DATA8, true).unwrap(), vec![0x60]); // GV0(0)
+    /// assert_eq!(cmd.allocate(DATA32, true).unwrap(), vec![0x64]); // GV0(4)
+    /// assert_eq!(cmd.allocate(DATA16, false).unwrap(), vec![0x40]); // LV0(0)
+    /// assert_eq!(cmd.reserved_bytes(), 8);
+    /// assert!(cmd.allocate(DATAN(1012), true).is_err());
+    /// assert!(cmd.allocate(DATAN(usize::MAX), true).is_err());
+    /// assert!(cmd.allocate(DATAS(usize::MAX), false).is_err());
+    /// println!("{}", cmd.layout());
+    /// ```
     pub fn allocate(&mut self, data: DataType, global: bool) -> Result<Vec<u8>, ValError> {
-        let local: u8 = (self.allocation >> 10) as u8;
-        let glob: u16 = self.allocation & ((1 << 10) - 1);
-        let address: u16 = if global { local.into() } else { glob };
-        let mem: u16 = match data {
-            DataType::DATA8 => 1,
-            DataType::DATA16 => 2,
-            DataType::DATA32 | DataType::DATAF => 4,
-            DataType::DATAN(length) => u16::try_from(length).unwrap(),
-            DataType::DATAS(length) => u16::try_from(length + 1).unwrap()
+        let (used, max, scope) = match global {
+            true => (self.global, GLOBAL_MAX, MemScope::Global),
+            false => (self.local, LOCAL_MAX, MemScope::Local)
         };
-        if local + (mem as u8) > (1 << 7) - 1 { return Err(ValError::MemOverflow(mem, local as u16, (1 << 7) - 1, "local".to_string())); }
-        if glob + mem > (1 << 10) - 1 { return Err(ValError::MemOverflow(mem, glob, (1 << 10) - 1, "global".to_string())); }
-        self.allocation += if global { mem } else { mem << 10 };
-        match address {
-            0..32 => {
-                if global { encode(Encoding::GV0(address as u8)) }
-                else { encode(Encoding::LV0(address as u8)) }
-            }
-            32..255 => {
-                if global { encode(Encoding::GV1(address as u8)) }
-                else { encode(Encoding::LV1(address as u8)) }
-            }
-            _ => {
-                if global { encode(Encoding::GV2(address)) }
-                else { Err(ValError::PosOverflow(mem.into(), (1 << 7) - 1)) } // Shouldn't be called
+        let offset = (used as usize).next_multiple_of(data.align());
+        if offset > max as usize || data.size() > max as usize - offset { return Err(ValError::MemOverflow(data.size(), used, max, scope)) }
+        let var = Variable { scope, offset: offset as u16, dtype: data };
+        let end = (offset + data.size()) as u16;
+        match global {
+            true => self.global = end,
+            false => self.local = end
+        }
+        self.variables.push(var);
+        var.encode()
+    }
+    /// Get memory layout of every allocated variable. For debugging
+    /// # Example
+    /// ```text
+    /// GLOBAL 8/1019 byte(s)
+    ///   0x000 DATA8    1 byte(s)
+    ///   0x004 DATA32   4 byte(s) (3 padding)
+    /// LOCAL 2/63 byte(s)
+    ///   0x000 DATA16   2 byte(s)
+    /// ```
+    pub fn layout(&self) -> String {
+        let mut map = String::new();
+        for (scope, used, max) in [(MemScope::Global, self.global, GLOBAL_MAX), (MemScope::Local, self.local, LOCAL_MAX)] {
+            map += &format!("{} {}/{} byte(s)\n", scope.to_string().to_uppercase(), used, max);
+            let mut end: u16 = 0;
+            for var in self.variables.iter().filter(|v| v.scope == scope) {
+                map += &format!("  {:#05X} {:<8} {} byte(s)", var.offset, var.dtype.to_string(), var.dtype.size());
+                if var.offset > end { map += &format!(" ({} padding)", var.offset - end); }
+                map += "\n";
+                end = var.offset + var.dtype.size() as u16;
             }
         }
+        map
     }
 }
 
@@ -286,7 +577,9 @@ impl Default for Command {
         Command {
             id: 170,
             reply: true, 
-            allocation: 0,
+            global: 0,
+            local: 0,
+            variables: vec![],
             bytecode: vec![],
         }
     }
@@ -0,0 +1,201 @@
+//! Mailbox messaging.
+//!
+//! EV3 programs exchange text, numeric (float) & logic messages through named mailboxes.
+//! Host writes mailbox with WRITEMAILBOX system command, brick programs use opMailbox OpCodes.
+//! Messages from brick arrive unsolicited, see [`crate::transport::Client`].
+//! # Example
+//! ```
+//! use ev3_dc::mailbox::{ MailboxMessage, MailboxValue };
+//!
+//! let msg = MailboxMessage::new("abc", MailboxValue::Text("hi".to_string())).unwrap();
+//! let packet = msg.to_command().unwrap().gen_bytes();
+//! assert_eq!(packet, vec![0x0E, 0x00, 0xAA, 0x00, 0x81, 0x9E, 0x04, b'a', b'b', b'c', 0x00, 0x03, 0x00, b'h', b'i', 0x00]);
+//! let back = MailboxMessage::parse(&packet).unwrap();
+//! assert_eq!(back.name, "abc");
+//! assert_eq!(back.text(), Some("hi"));
+//! ```
+
+use alloc::{ string::{ String, ToString }, vec, vec::Vec };
+use crate::{ encode, Command, DataType, Encoding::*, ValError };
+use crate::error::ReplyError;
+use crate::system::{ SystemCommand, WRITEMAILBOX };
+use crate::utils::{ auto_const, ChainByte };
+
+/// Mailbox message value
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MailboxValue {
+    /// Zero-terminated text
+    Text(String),
+    /// IEEE-754 single precision float
+    Numeric(f32),
+    /// Boolean as single byte
+    Logic(bool),
+}
+
+impl MailboxValue {
+    /// Encode value as mailbox payload
+    pub fn encode(&self) -> Result<Vec<u8>, ValError> {
+        match self {
+            MailboxValue::Text(text) => {
+                if text.contains('\0') { return Err(ValError::InvalidString(text.clone())) }
+                let mut bytes = text.as_bytes().to_vec();
+                bytes.push(0);
+                Ok(bytes)
+            }
+            MailboxValue::Numeric(val) => Ok(val.to_le_bytes().to_vec()),
+            MailboxValue::Logic(val) => Ok(vec![*val as u8])
+        }
+    }
+}
+
+/// Mailbox message with raw payload. Type of payload is known only to receiver
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MailboxMessage {
+    /// Mailbox name
+    pub name: String,
+    /// Raw payload
+    pub payload: Vec<u8>,
+}
+
+impl MailboxMessage {
+    /// Create message from value
+    pub fn new(name: &str, value: MailboxValue) -> Result<Self, ValError> {
+        if name.contains('\0') { return Err(ValError::InvalidString(name.to_string())) }
+        if name.len() > 254 { return Err(ValError::InvalidRange(name.len() as i32, 1, 254)) }
+        Ok(MailboxMessage { name: name.to_string(), payload: value.encode()? })
+    }
+    /// Create WRITEMAILBOX system command without reply
+    pub fn to_command(&self) -> Result<SystemCommand, ValError> {
+        if self.payload.len() > u16::MAX as usize { return Err(ValError::InvalidRange(self.payload.len() as i32, 0, u16::MAX as i32)) }
+        let mut payload = vec![(self.name.len() + 1) as u8];
+        payload.extend(self.name.as_bytes());
+        payload.push(0);
+        payload.extend((self.payload.len() as u16).to_le_bytes());
+        payload.extend(&self.payload);
+        let mut cmd = SystemCommand::new(WRITEMAILBOX, payload);
+        cmd.reply = false;
+        Ok(cmd)
+    }
+    /// Parse WRITEMAILBOX packet
+    pub fn parse(packet: &[u8]) -> Result<Self, ReplyError> {
+        if packet.len() < 7 { return Err(ReplyError::Truncated(packet.len(), 7)) }
+        if !is_mailbox(packet) { return Err(ReplyError::UnknownType(packet[4])) }
+        let name_len = packet[6] as usize;
+        let size_at = 7 + name_len;
+        if packet.len() < size_at + 2 { return Err(ReplyError::Truncated(packet.len(), size_at + 2)) }
+        let name = String::from_utf8_lossy(&packet[7..size_at]).trim_end_matches('\0').to_string();
+        let size = u16::from_le_bytes([packet[size_at], packet[size_at + 1]]) as usize;
+        let end = size_at + 2 + size;
+        if packet.len() < end { return Err(ReplyError::Truncated(packet.len(), end)) }
+        Ok(MailboxMessage { name, payload: packet[(size_at + 2)..end].to_vec() })
+    }
+    /// Get payload as text
+    pub fn text(&self) -> Option<&str> {
+        str::from_utf8(&self.payload).ok()?.split_terminator('\0').next().or(Some(""))
+    }
+    /// Get payload as numeric
+    pub fn numeric(&self) -> Option<f32> {
+        Some(f32::from_le_bytes(self.payload.as_slice().try_into().ok()?))
+    }
+    /// Get payload as logic
+    pub fn logic(&self) -> Option<bool> {
+        match self.payload.as_slice() {
+            [val] => Some(*val != 0),
+            _ => None
+        }
+    }
+}
+
+/// Check if packet is WRITEMAILBOX system command
+pub fn is_mailbox(packet: &[u8]) -> bool {
+    packet.len() > 5 && (packet[4] & 0x7F) == 0x01 && packet[5] == WRITEMAILBOX
+}
+
+// Mailbox type parameter
+fn mailbox_type(dtype: DataType) -> Result<i8, ValError> {
+    match dtype {
+        DataType::DATA8 => Ok(0),
+        DataType::DATA16 => Ok(1),
+        DataType::DATA32 => Ok(2),
+        DataType::DATAF => Ok(3),
+        DataType::DATAS(_) => Ok(4),
+        DataType::DATAN(_) => Err(ValError::InvalidType(dtype))
+    }
+}
+
+/// Open mailbox `no` (0 - 29) on brick
+pub fn mailbox_open(no: u8, name: &str, dtype: DataType, fifo: u8, values: u8) -> Result<Vec<u8>, ValError> {
+    if no > 29 { return Err(ValError::InvalidRange(no as i32, 0, 29)) }
+    let mut byte = ChainByte::new();
+    byte.push(0xD8) // opMailbox_Open
+        .add(encode(LC0(no as i8))?)
+        .add(encode(LCS(name))?)
+        .add(encode(LC0(mailbox_type(dtype)?))?)
+        .add(auto_const(fifo as i32)?)
+        .add(auto_const(values as i32)?);
+    Ok(byte.bytes)
+}
+
+/// Write mailbox of another brick by name, over hardware (1: USB, 2: Bluetooth, 3: WiFi). \
+/// `values` are encoded parameters
+pub fn mailbox_write(brick: &str, hardware: u8, name: &str, dtype: DataType, values: &[Vec<u8>]) -> Result<Vec<u8>, ValError> {
+    let mut byte = ChainByte::new();
+    byte.push(0xD9) // opMailbox_Write
+        .add(encode(LCS(brick))?)
+        .add(encode(LC0(hardware as i8))?)
+        .add(encode(LCS(name))?)
+        .add(encode(LC0(mailbox_type(dtype)?))?)
+        .add(auto_const(values.len() as i32)?);
+    for val in values { byte.add(val.clone()); }
+    Ok(byte.bytes)
+}
+
+/// Read value from opened mailbox `no`
+pub fn mailbox_read(cmd: &mut Command, no: u8, dtype: DataType) -> Result<(Vec<u8>, Vec<DataType>), ValError> {
+    if no > 29 { return Err(ValError::InvalidRange(no as i32, 0, 29)) }
+    let mut byte = ChainByte::new();
+    byte.push(0xDA) // opMailbox_Read
+        .add(encode(LC0(no as i8))?)
+        .add(encode(LC2(dtype.size() as i16))?)
+        .add(encode(LC0(1))?)
+        .add(cmd.allocate(dtype, true)?);
+    Ok((byte.bytes, vec![dtype]))
+}
+
+/// Test if mailbox `no` is busy (no new message)
+pub fn mailbox_test(cmd: &mut Command, no: u8) -> Result<(Vec<u8>, Vec<DataType>), ValError> {
+    if no > 29 { return Err(ValError::InvalidRange(no as i32, 0, 29)) }
+    let mut byte = ChainByte::new();
+    byte.push(0xDB) // opMailbox_Test
+        .add(encode(LC0(no as i8))?)
+        .add(cmd.allocate(DataType::DATA8, true)?);
+    Ok((byte.bytes, vec![DataType::DATA8]))
+}
+
+/// Wait until mailbox `no` received message
+pub fn mailbox_ready(no: u8) -> Result<Vec<u8>, ValError> {
+    if no > 29 { return Err(ValError::InvalidRange(no as i32, 0, 29)) }
+    let mut byte = ChainByte::new();
+    byte.push(0xDC) // opMailbox_Ready
+        .add(encode(LC0(no as i8))?);
+    Ok(byte.bytes)
+}
+
+/// Close mailbox `no`
+/// # Example
+/// ```
+/// use ev3_dc::mailbox::{ mailbox_close, mailbox_ready };
+///
+/// assert_eq!(mailbox_close(29).unwrap(), vec![0xDD, 0x1D]);
+/// assert!(mailbox_close(30).is_err());
+/// assert!(mailbox_ready(224).is_err());
+/// ```
+pub fn mailbox_close(no: u8) -> Result<Vec<u8>, ValError> {
+    if no > 29 { return Err(ValError::InvalidRange(no as i32, 0, 29)) }
+    let mut byte = ChainByte::new();
+    byte.push(0xDD) // opMailbox_Close
+        .add(encode(LC0(no as i8))?);
+    Ok(byte.bytes)
+}
@@ -0,0 +1,694 @@
+//! OpCode catalogue & disassembler.
+//!
+//! Catalogue of VM OpCodes with their sub-commands and parameter layout.
+//! Used by [`disassemble`] to split bytecode back into instructions.
+//! # Example
+//! Disassemble green blinking LED bytecode
+//! ```
+//! use ev3_dc::opcode::disassemble;
+//!
+//! let code = disassemble(&[0x82, 0x1B, 0x04]).unwrap();
+//! assert_eq!(code[0].to_string(), "0000  UI_WRITE.LED LC0(4)");
+//! ```
+
+use alloc::{ vec, vec::Vec };
+use core::fmt;
+use crate::{ decode, Encoding, ValError };
+
+/// Parameter kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Param {
+    /// Constant or variable
+    Value,
+    /// Jump offset, relative to end of instruction
+    Label,
+    /// Number of following parameters
+    Count,
+}
+
+/// Parameter layout of OpCode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Params {
+    /// Fixed parameter list
+    Fixed(&'static [Param]),
+    /// First parameter is sub-command
+    Sub(&'static [SubInfo]),
+}
+
+/// Sub-command information
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubInfo {
+    /// Sub-command byte
+    pub code: u8,
+    /// Sub-command name as in firmware documentation
+    pub name: &'static str,
+    /// Parameters after sub-command
+    pub params: &'static [Param],
+}
+
+/// OpCode information
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpInfo {
+    /// OpCode byte
+    pub code: u8,
+    /// OpCode name without `op` prefix
+    pub name: &'static str,
+    /// Parameter layout
+    pub params: Params,
+}
+
+impl OpInfo {
+    /// Find sub-command by byte
+    pub fn sub(&self, code: u8) -> Option<&'static SubInfo> {
+        match self.params {
+            Params::Sub(subs) => subs.iter().find(|s| s.code == code),
+            Params::Fixed(_) => None
+        }
+    }
+    /// Find sub-command by name (case-insensitive)
+    pub fn sub_by_name(&self, name: &str) -> Option<&'static SubInfo> {
+        match self.params {
+            Params::Sub(subs) => subs.iter().find(|s| s.name.eq_ignore_ascii_case(name)),
+            Params::Fixed(_) => None
+        }
+    }
+}
+
+/// Find OpCode by byte
+pub fn lookup(code: u8) -> Option<&'static OpInfo> {
+    OPCODES.iter().find(|op| op.code == code)
+}
+
+/// Find OpCode by name (case-insensitive, `op` prefix is optional)
+pub fn by_name(name: &str) -> Option<&'static OpInfo> {
+    let name = name.strip_prefix("op").unwrap_or(name);
+    OPCODES.iter().find(|op| op.name.eq_ignore_ascii_case(name))
+}
+
+/// Every known OpCode
+pub fn catalogue() -> &'static [OpInfo] {
+    OPCODES
+}
+
+/// Decoded instruction
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instruction<'a> {
+    /// Byte offset of OpCode
+    pub offset: usize,
+    /// Length in bytes, including parameters
+    pub length: usize,
+    /// OpCode
+    pub op: &'static OpInfo,
+    /// Sub-command
+    pub sub: Option<&'static SubInfo>,
+    /// Parameters, excluding sub-command
+    pub params: Vec<Encoding<'a>>,
+    /// Absolute jump target for jumping OpCode
+    pub target: Option<usize>,
+}
+
+impl fmt::Display for Instruction<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}  {}", self.offset, self.op.name)?;
+        if let Some(sub) = self.sub { write!(f, ".{}", sub.name)?; }
+        for (i, param) in self.params.iter().enumerate() {
+            write!(f, "{}{:?}", if i == 0 { " " } else { ", " }, param)?;
+        }
+        if let Some(target) = self.target { write!(f, " -> {:04}", target)?; }
+        Ok(())
+    }
+}
+
+/// Disassemble bytecode into instructions
+/// # Example
+/// ```
+/// use ev3_dc::opcode::disassemble;
+///
+/// // opOutput_Speed LC0(0) LC0(3) LC1(-50), opOutput_Start LC0(0) LC0(3)
+/// let code = disassemble(&[0xA5, 0x00, 0x03, 0x81, 0xCE, 0xA6, 0x00, 0x03]).unwrap();
+/// assert_eq!(code.len(), 2);
+/// assert_eq!(code[0].to_string(), "0000  OUTPUT_SPEED LC0(0), LC0(3), LC1(-50)");
+/// assert_eq!(code[1].offset, 5);
+///
+/// // opInit_Bytes LV0(0) LC0(3) LC0(1) LC0(2) LC0(3), opNop
+/// let code = disassemble(&[0x2F, 0x40, 0x03, 0x01, 0x02, 0x03, 0x01]).unwrap();
+/// assert_eq!(code[0].to_string(), "0000  INIT_BYTES LV0(0), LC0(3), LC0(1), LC0(2), LC0(3)");
+/// assert_eq!(code[1].offset, 6);
+/// assert!(disassemble(&[0x23]).is_err()); // Not defined by firmware
+/// ```
+pub fn disassemble(bytes: &[u8]) -> Result<Vec<Instruction<'_>>, ValError> {
+    let mut code = vec![];
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let inst = instruction(bytes, pos)?;
+        pos += inst.length;
+        code.push(inst);
+    }
+    Ok(code)
+}
+
+/// Decode single instruction at byte offset `start`
+pub fn instruction(bytes: &[u8], start: usize) -> Result<Instruction<'_>, ValError> {
+    if start >= bytes.len() { return Err(ValError::Incomplete(bytes.len(), start + 1)) }
+    let op = lookup(bytes[start]).ok_or(ValError::UnknownOpCode(bytes[start]))?;
+    let mut pos = start + 1;
+    let next = |pos: &mut usize| -> Result<Encoding<'_>, ValError> {
+        let (enc, len) = decode(&bytes[*pos..])?;
+        *pos += len;
+        Ok(enc)
+    };
+    let (sub, layout) = match op.params {
+        Params::Fixed(layout) => (None, layout),
+        Params::Sub(_) => {
+            let code = next(&mut pos)?.as_int().ok_or(ValError::Decode(bytes[pos - 1]))?;
+            let sub = op.sub(code as u8).ok_or(ValError::UnknownSubCode(op.code, code as u8))?;
+            (Some(sub), sub.params)
+        }
+    };
+    let mut params = vec![];
+    let mut jump = None;
+    for kind in layout {
+        let enc = next(&mut pos)?;
+        params.push(enc);
+        match kind {
+            Param::Value => {}
+            Param::Label => jump = enc.as_int(),
+            Param::Count => for _ in 0..enc.as_int().unwrap_or(0) {
+                params.push(next(&mut pos)?);
+            }
+        }
+    }
+    let target = jump.map(|offset| (pos as i64 + offset as i64) as usize);
+    Ok(Instruction { offset: start, length: pos - start, op, sub, params, target })
+}
+
+const V: Param = Param::Value;
+const L: Param = Param::Label;
+const N: Param = Param::Count;
+
+const fn op(code: u8, name: &'static str, params: Params) -> OpInfo {
+    OpInfo { code, name, params }
+}
+
+const fn sub(code: u8, name: &'static str, params: &'static [Param]) -> SubInfo {
+    SubInfo { code, name, params }
+}
+
+static OPCODES: &[OpInfo] = &[
+    op(0x00, "ERROR", Params::Fixed(&[])),
+    op(0x01, "NOP", Params::Fixed(&[])),
+    op(0x02, "PROGRAM_STOP", Params::Fixed(&[V])),
+    op(0x03, "PROGRAM_START", Params::Fixed(&[V, V, V, V])),
+    op(0x04, "OBJECT_STOP", Params::Fixed(&[V])),
+    op(0x05, "OBJECT_START", Params::Fixed(&[V])),
+    op(0x06, "OBJECT_TRIG", Params::Fixed(&[V])),
+    op(0x07, "OBJECT_WAIT", Params::Fixed(&[V])),
+    op(0x08, "RETURN", Params::Fixed(&[])),
+    op(0x09, "CALL", Params::Fixed(&[V, N])),
+    op(0x0A, "OBJECT_END", Params::Fixed(&[])),
+    op(0x0B, "SLEEP", Params::Fixed(&[])),
+    op(0x0C, "PROGRAM_INFO", Params::Sub(SUB_PROGRAM_INFO)),
+    op(0x0D, "LABEL", Params::Fixed(&[V])),
+    op(0x0E, "PROBE", Params::Fixed(&[V, V, V, V])),
+    op(0x0F, "DO", Params::Fixed(&[V, V, V])),
+    op(0x10, "ADD8", Params::Fixed(&[V, V, V])),
+    op(0x11, "ADD16", Params::Fixed(&[V, V, V])),
+    op(0x12, "ADD32", Params::Fixed(&[V, V, V])),
+    op(0x13, "ADDF", Params::Fixed(&[V, V, V])),
+    op(0x14, "SUB8", Params::Fixed(&[V, V, V])),
+    op(0x15, "SUB16", Params::Fixed(&[V, V, V])),
+    op(0x16, "SUB32", Params::Fixed(&[V, V, V])),
+    op(0x17, "SUBF", Params::Fixed(&[V, V, V])),
+    op(0x18, "MUL8", Params::Fixed(&[V, V, V])),
+    op(0x19, "MUL16", Params::Fixed(&[V, V, V])),
+    op(0x1A, "MUL32", Params::Fixed(&[V, V, V])),
+    op(0x1B, "MULF", Params::Fixed(&[V, V, V])),
+    op(0x1C, "DIV8", Params::Fixed(&[V, V, V])),
+    op(0x1D, "DIV16", Params::Fixed(&[V, V, V])),
+    op(0x1E, "DIV32", Params::Fixed(&[V, V, V])),
+    op(0x1F, "DIVF", Params::Fixed(&[V, V, V])),
+    op(0x20, "OR8", Params::Fixed(&[V, V, V])),
+    op(0x21, "OR16", Params::Fixed(&[V, V, V])),
+    op(0x22, "OR32", Params::Fixed(&[V, V, V])),
+    op(0x24, "AND8", Params::Fixed(&[V, V, V])),
+    op(0x25, "AND16", Params::Fixed(&[V, V, V])),
+    op(0x26, "AND32", Params::Fixed(&[V, V, V])),
+    op(0x28, "XOR8", Params::Fixed(&[V, V, V])),
+    op(0x29, "XOR16", Params::Fixed(&[V, V, V])),
+    op(0x2A, "XOR32", Params::Fixed(&[V, V, V])),
+    op(0x2C, "RL8", Params::Fixed(&[V, V, V])),
+    op(0x2D, "RL16", Params::Fixed(&[V, V, V])),
+    op(0x2E, "RL32", Params::Fixed(&[V, V, V])),
+    op(0x2F, "INIT_BYTES", Params::Fixed(&[V, N])),
+    op(0x30, "MOVE8_8", Params::Fixed(&[V, V])),
+    op(0x31, "MOVE8_16", Params::Fixed(&[V, V])),
+    op(0x32, "MOVE8_32", Params::Fixed(&[V, V])),
+    op(0x33, "MOVE8_F", Params::Fixed(&[V, V])),
+    op(0x34, "MOVE16_8", Params::Fixed(&[V, V])),
+    op(0x35, "MOVE16_16", Params::Fixed(&[V, V])),
+    op(0x36, "MOVE16_32", Params::Fixed(&[V, V])),
+    op(0x37, "MOVE16_F", Params::Fixed(&[V, V])),
+    op(0x38, "MOVE32_8", Params::Fixed(&[V, V])),
+    op(0x39, "MOVE32_16", Params::Fixed(&[V, V])),
+    op(0x3A, "MOVE32_32", Params::Fixed(&[V, V])),
+    op(0x3B, "MOVE32_F", Params::Fixed(&[V, V])),
+    op(0x3C, "MOVEF_8", Params::Fixed(&[V, V])),
+    op(0x3D, "MOVEF_16", Params::Fixed(&[V, V])),
+    op(0x3E, "MOVEF_32", Params::Fixed(&[V, V])),
+    op(0x3F, "MOVEF_F", Params::Fixed(&[V, V])),
+    op(0x40, "JR", Params::Fixed(&[L])),
+    op(0x41, "JR_FALSE", Params::Fixed(&[V, L])),
+    op(0x42, "JR_TRUE", Params::Fixed(&[V, L])),
+    op(0x43, "JR_NAN", Params::Fixed(&[V, L])),
+    op(0x44, "CP_LT8", Params::Fixed(&[V, V, V])),
+    op(0x45, "CP_LT16", Params::Fixed(&[V, V, V])),
+    op(0x46, "CP_LT32", Params::Fixed(&[V, V, V])),
+    op(0x47, "CP_LTF", Params::Fixed(&[V, V, V])),
+    op(0x48, "CP_GT8", Params::Fixed(&[V, V, V])),
+    op(0x49, "CP_GT16", Params::Fixed(&[V, V, V])),
+    op(0x4A, "CP_GT32", Params::Fixed(&[V, V, V])),
+    op(0x4B, "CP_GTF", Params::Fixed(&[V, V, V])),
+    op(0x4C, "CP_EQ8", Params::Fixed(&[V, V, V])),
+    op(0x4D, "CP_EQ16", Params::Fixed(&[V, V, V])),
+    op(0x4E, "CP_EQ32", Params::Fixed(&[V, V, V])),
+    op(0x4F, "CP_EQF", Params::Fixed(&[V, V, V])),
+    op(0x50, "CP_NEQ8", Params::Fixed(&[V, V, V])),
+    op(0x51, "CP_NEQ16", Params::Fixed(&[V, V, V])),
+    op(0x52, "CP_NEQ32", Params::Fixed(&[V, V, V])),
+    op(0x53, "CP_NEQF", Params::Fixed(&[V, V, V])),
+    op(0x54, "CP_LTEQ8", Params::Fixed(&[V, V, V])),
+    op(0x55, "CP_LTEQ16", Params::Fixed(&[V, V, V])),
+    op(0x56, "CP_LTEQ32", Params::Fixed(&[V, V, V])),
+    op(0x57, "CP_LTEQF", Params::Fixed(&[V, V, V])),
+    op(0x58, "CP_GTEQ8", Params::Fixed(&[V, V, V])),
+    op(0x59, "CP_GTEQ16", Params::Fixed(&[V, V, V])),
+    op(0x5A, "CP_GTEQ32", Params::Fixed(&[V, V, V])),
+    op(0x5B, "CP_GTEQF", Params::Fixed(&[V, V, V])),
+    op(0x5C, "SELECT8", Params::Fixed(&[V, V, V, V])),
+    op(0x5D, "SELECT16", Params::Fixed(&[V, V, V, V])),
+    op(0x5E, "SELECT32", Params::Fixed(&[V, V, V, V])),
+    op(0x5F, "SELECTF", Params::Fixed(&[V, V, V, V])),
+    op(0x60, "SYSTEM", Params::Fixed(&[V, V])),
+    op(0x61, "PORT_CNV_OUTPUT", Params::Fixed(&[V, V, V, V])),
+    op(0x62, "PORT_CNV_INPUT", Params::Fixed(&[V, V, V])),
+    op(0x63, "NOTE_TO_FREQ", Params::Fixed(&[V, V])),
+    op(0x64, "JR_LT8", Params::Fixed(&[V, V, L])),
+    op(0x65, "JR_LT16", Params::Fixed(&[V, V, L])),
+    op(0x66, "JR_LT32", Params::Fixed(&[V, V, L])),
+    op(0x67, "JR_LTF", Params::Fixed(&[V, V, L])),
+    op(0x68, "JR_GT8", Params::Fixed(&[V, V, L])),
+    op(0x69, "JR_GT16", Params::Fixed(&[V, V, L])),
+    op(0x6A, "JR_GT32", Params::Fixed(&[V, V, L])),
+    op(0x6B, "JR_GTF", Params::Fixed(&[V, V, L])),
+    op(0x6C, "JR_EQ8", Params::Fixed(&[V, V, L])),
+    op(0x6D, "JR_EQ16", Params::Fixed(&[V, V, L])),
+    op(0x6E, "JR_EQ32", Params::Fixed(&[V, V, L])),
+    op(0x6F, "JR_EQF", Params::Fixed(&[V, V, L])),
+    op(0x70, "JR_NEQ8", Params::Fixed(&[V, V, L])),
+    op(0x71, "JR_NEQ16", Params::Fixed(&[V, V, L])),
+    op(0x72, "JR_NEQ32", Params::Fixed(&[V, V, L])),
+    op(0x73, "JR_NEQF", Params::Fixed(&[V, V, L])),
+    op(0x74, "JR_LTEQ8", Params::Fixed(&[V, V, L])),
+    op(0x75, "JR_LTEQ16", Params::Fixed(&[V, V, L])),
+    op(0x76, "JR_LTEQ32", Params::Fixed(&[V, V, L])),
+    op(0x77, "JR_LTEQF", Params::Fixed(&[V, V, L])),
+    op(0x78, "JR_GTEQ8", Params::Fixed(&[V, V, L])),
+    op(0x79, "JR_GTEQ16", Params::Fixed(&[V, V, L])),
+    op(0x7A, "JR_GTEQ32", Params::Fixed(&[V, V, L])),
+    op(0x7B, "JR_GTEQF", Params::Fixed(&[V, V, L])),
+    op(0x7C, "INFO", Params::Sub(SUB_INFO)),
+    op(0x7D, "STRINGS", Params::Sub(SUB_STRINGS)),
+    op(0x7E, "MEMORY_WRITE", Params::Fixed(&[V, V, V, V, V])),
+    op(0x7F, "MEMORY_READ", Params::Fixed(&[V, V, V, V, V])),
+    op(0x80, "UI_FLUSH", Params::Fixed(&[])),
+    op(0x81, "UI_READ", Params::Sub(SUB_UI_READ)),
+    op(0x82, "UI_WRITE", Params::Sub(SUB_UI_WRITE)),
+    op(0x83, "UI_BUTTON", Params::Sub(SUB_UI_BUTTON)),
+    op(0x84, "UI_DRAW", Params::Sub(SUB_UI_DRAW)),
+    op(0x85, "TIMER_WAIT", Params::Fixed(&[V, V])),
+    op(0x86, "TIMER_READY", Params::Fixed(&[V])),
+    op(0x87, "TIMER_READ", Params::Fixed(&[V])),
+    op(0x88, "BP0", Params::Fixed(&[])),
+    op(0x89, "BP1", Params::Fixed(&[])),
+    op(0x8A, "BP2", Params::Fixed(&[])),
+    op(0x8B, "BP3", Params::Fixed(&[])),
+    op(0x8C, "BP_SET", Params::Fixed(&[V, V, V])),
+    op(0x8D, "MATH", Params::Sub(SUB_MATH)),
+    op(0x8E, "RANDOM", Params::Fixed(&[V, V, V])),
+    op(0x8F, "TIMER_READ_US", Params::Fixed(&[V])),
+    op(0x90, "KEEP_ALIVE", Params::Fixed(&[V])),
+    op(0x94, "SOUND", Params::Sub(SUB_SOUND)),
+    op(0x95, "SOUND_TEST", Params::Fixed(&[V])),
+    op(0x96, "SOUND_READY", Params::Fixed(&[])),
+    op(0x98, "INPUT_DEVICE_LIST", Params::Fixed(&[V, V, V])),
+    op(0x99, "INPUT_DEVICE", Params::Sub(SUB_INPUT_DEVICE)),
+    op(0x9A, "INPUT_READ", Params::Fixed(&[V, V, V, V, V])),
+    op(0x9B, "INPUT_TEST", Params::Fixed(&[V, V, V])),
+    op(0x9C, "INPUT_READY", Params::Fixed(&[V, V])),
+    op(0x9D, "INPUT_READSI", Params::Fixed(&[V, V, V, V, V])),
+    op(0x9E, "INPUT_READEXT", Params::Fixed(&[V, V, V, V, V, N])),
+    op(0x9F, "INPUT_WRITE", Params::Fixed(&[V, V, V, V])),
+    op(0xA0, "OUTPUT_GET_TYPE", Params::Fixed(&[V, V, V])),
+    op(0xA1, "OUTPUT_SET_TYPE", Params::Fixed(&[V, V, V])),
+    op(0xA2, "OUTPUT_RESET", Params::Fixed(&[V, V])),
+    op(0xA3, "OUTPUT_STOP", Params::Fixed(&[V, V, V])),
+    op(0xA4, "OUTPUT_POWER", Params::Fixed(&[V, V, V])),
+    op(0xA5, "OUTPUT_SPEED", Params::Fixed(&[V, V, V])),
+    op(0xA6, "OUTPUT_START", Params::Fixed(&[V, V])),
+    op(0xA7, "OUTPUT_POLARITY", Params::Fixed(&[V, V, V])),
+    op(0xA8, "OUTPUT_READ", Params::Fixed(&[V, V, V, V])),
+    op(0xA9, "OUTPUT_TEST", Params::Fixed(&[V, V, V])),
+    op(0xAA, "OUTPUT_READY", Params::Fixed(&[V, V])),
+    op(0xAB, "OUTPUT_POSITION", Params::Fixed(&[V, V, V])),
+    op(0xAC, "OUTPUT_STEP_POWER", Params::Fixed(&[V, V, V, V, V, V, V])),
+    op(0xAD, "OUTPUT_TIME_POWER", Params::Fixed(&[V, V, V, V, V, V, V])),
+    op(0xAE, "OUTPUT_STEP_SPEED", Params::Fixed(&[V, V, V, V, V, V, V])),
+    op(0xAF, "OUTPUT_TIME_SPEED", Params::Fixed(&[V, V, V, V, V, V, V])),
+    op(0xB0, "OUTPUT_STEP_SYNC", Params::Fixed(&[V, V, V, V, V, V])),
+    op(0xB1, "OUTPUT_TIME_SYNC", Params::Fixed(&[V, V, V, V, V, V])),
+    op(0xB2, "OUTPUT_CLR_COUNT", Params::Fixed(&[V, V])),
+    op(0xB3, "OUTPUT_GET_COUNT", Params::Fixed(&[V, V, V])),
+    op(0xB4, "OUTPUT_PRG_STOP", Params::Fixed(&[])),
+    op(0xC0, "FILE", Params::Sub(SUB_FILE)),
+    op(0xC1, "ARRAY", Params::Sub(SUB_ARRAY)),
+    op(0xC2, "ARRAY_WRITE", Params::Fixed(&[V, V, V])),
+    op(0xC3, "ARRAY_READ", Params::Fixed(&[V, V, V])),
+    op(0xC4, "ARRAY_APPEND", Params::Fixed(&[V, V])),
+    op(0xC5, "MEMORY_USAGE", Params::Fixed(&[V, V])),
+    op(0xC6, "FILENAME", Params::Sub(SUB_FILENAME)),
+    op(0xC8, "READ8", Params::Fixed(&[V, V, V])),
+    op(0xC9, "READ16", Params::Fixed(&[V, V, V])),
+    op(0xCA, "READ32", Params::Fixed(&[V, V, V])),
+    op(0xCB, "READF", Params::Fixed(&[V, V, V])),
+    op(0xCC, "WRITE8", Params::Fixed(&[V, V, V])),
+    op(0xCD, "WRITE16", Params::Fixed(&[V, V, V])),
+    op(0xCE, "WRITE32", Params::Fixed(&[V, V, V])),
+    op(0xCF, "WRITEF", Params::Fixed(&[V, V, V])),
+    op(0xD0, "COM_READY", Params::Fixed(&[V, V])),
+    op(0xD1, "COM_READDATA", Params::Fixed(&[V, V, V, V])),
+    op(0xD2, "COM_WRITEDATA", Params::Fixed(&[V, V, V, V])),
+    op(0xD3, "COM_GET", Params::Sub(SUB_COM_GET)),
+    op(0xD4, "COM_SET", Params::Sub(SUB_COM_SET)),
+    op(0xD5, "COM_TEST", Params::Fixed(&[V, V, V])),
+    op(0xD6, "COM_REMOVE", Params::Fixed(&[V, V])),
+    op(0xD7, "COM_WRITEFILE", Params::Fixed(&[V, V, V, V])),
+    op(0xD8, "MAILBOX_OPEN", Params::Fixed(&[V, V, V, V, V])),
+    op(0xD9, "MAILBOX_WRITE", Params::Fixed(&[V, V, V, V, N])),
+    op(0xDA, "MAILBOX_READ", Params::Fixed(&[V, V, N])),
+    op(0xDB, "MAILBOX_TEST", Params::Fixed(&[V, V])),
+    op(0xDC, "MAILBOX_READY", Params::Fixed(&[V])),
+    op(0xDD, "MAILBOX_CLOSE", Params::Fixed(&[V]))
+];
+
+static SUB_PROGRAM_INFO: &[SubInfo] = &[
+    sub(0, "OBJ_STOP", &[V, V]),
+    sub(4, "OBJ_START", &[V, V]),
+    sub(22, "GET_STATUS", &[V, V]),
+    sub(23, "GET_SPEED", &[V, V]),
+    sub(24, "GET_PRGRESULT", &[V, V]),
+    sub(25, "SET_INSTR", &[V])
+];
+
+static SUB_INFO: &[SubInfo] = &[
+    sub(1, "SET_ERROR", &[V]),
+    sub(2, "GET_ERROR", &[V]),
+    sub(3, "ERRORTEXT", &[V, V, V]),
+    sub(4, "GET_VOLUME", &[V]),
+    sub(5, "SET_VOLUME", &[V]),
+    sub(6, "GET_MINUTES", &[V]),
+    sub(7, "SET_MINUTES", &[V])
+];
+
+static SUB_STRINGS: &[SubInfo] = &[
+    sub(1, "GET_SIZE", &[V, V]),
+    sub(2, "ADD", &[V, V, V]),
+    sub(3, "COMPARE", &[V, V, V]),
+    sub(5, "DUPLICATE", &[V, V]),
+    sub(6, "VALUE_TO_STRING", &[V, V, V, V]),
+    sub(7, "STRING_TO_VALUE", &[V, V]),
+    sub(8, "STRIP", &[V, V]),
+    sub(9, "NUMBER_TO_STRING", &[V, V, V]),
+    sub(10, "SUB", &[V, V, V]),
+    sub(11, "VALUE_FORMATTED", &[V, V, V, V]),
+    sub(12, "NUMBER_FORMATTED", &[V, V, V, V])
+];
+
+static SUB_UI_READ: &[SubInfo] = &[
+    sub(1, "GET_VBATT", &[V]),
+    sub(2, "GET_IBATT", &[V]),
+    sub(3, "GET_OS_VERS", &[V, V]),
+    sub(4, "GET_EVENT", &[V]),
+    sub(5, "GET_TBATT", &[V]),
+    sub(6, "GET_IINT", &[V]),
+    sub(7, "GET_IMOTOR", &[V]),
+    sub(8, "GET_STRING", &[V, V]),
+    sub(9, "GET_HW_VERS", &[V, V]),
+    sub(10, "GET_FW_VERS", &[V, V]),
+    sub(11, "GET_FW_BUILD", &[V, V]),
+    sub(12, "GET_OS_BUILD", &[V, V]),
+    sub(13, "GET_ADDRESS", &[V]),
+    sub(14, "GET_CODE", &[V, V, V, V]),
+    sub(15, "KEY", &[V]),
+    sub(16, "GET_SHUTDOWN", &[V]),
+    sub(17, "GET_WARNING", &[V]),
+    sub(18, "GET_LBATT", &[V]),
+    sub(21, "TEXTBOX_READ", &[V, V, V, V, V, V]),
+    sub(26, "GET_VERSION", &[V, V]),
+    sub(27, "GET_IP", &[V, V]),
+    sub(29, "GET_POWER", &[V, V, V, V]),
+    sub(30, "GET_SDCARD", &[V, V, V]),
+    sub(31, "GET_USBSTICK", &[V, V, V])
+];
+
+static SUB_UI_WRITE: &[SubInfo] = &[
+    sub(1, "WRITE_FLUSH", &[]),
+    sub(2, "FLOATVALUE", &[V, V, V]),
+    sub(3, "STAMP", &[V]),
+    sub(8, "PUT_STRING", &[V]),
+    sub(9, "VALUE8", &[V]),
+    sub(10, "VALUE16", &[V]),
+    sub(11, "VALUE32", &[V]),
+    sub(12, "VALUEF", &[V]),
+    sub(13, "ADDRESS", &[V]),
+    sub(14, "CODE", &[V, V]),
+    sub(15, "DOWNLOAD_END", &[]),
+    sub(16, "SCREEN_BLOCK", &[V]),
+    sub(21, "TEXTBOX_APPEND", &[V, V, V, V]),
+    sub(22, "SET_BUSY", &[V]),
+    sub(24, "SET_TESTPIN", &[V]),
+    sub(25, "INIT_RUN", &[]),
+    sub(26, "UPDATE_RUN", &[]),
+    sub(27, "LED", &[V]),
+    sub(29, "POWER", &[V]),
+    sub(30, "GRAPH_SAMPLE", &[]),
+    sub(31, "TERMINAL", &[V])
+];
+
+static SUB_UI_BUTTON: &[SubInfo] = &[
+    sub(1, "SHORTPRESS", &[V, V]),
+    sub(2, "LONGPRESS", &[V, V]),
+    sub(3, "WAIT_FOR_PRESS", &[]),
+    sub(4, "FLUSH", &[]),
+    sub(5, "PRESS", &[V]),
+    sub(6, "RELEASE", &[V]),
+    sub(7, "GET_HORZ", &[V]),
+    sub(8, "GET_VERT", &[V]),
+    sub(9, "PRESSED", &[V, V]),
+    sub(10, "SET_BACK_BLOCK", &[V]),
+    sub(11, "GET_BACK_BLOCK", &[V]),
+    sub(12, "TESTSHORTPRESS", &[V, V]),
+    sub(13, "TESTLONGPRESS", &[V, V]),
+    sub(14, "GET_BUMPED", &[V, V]),
+    sub(15, "GET_CLICK", &[V])
+];
+
+static SUB_UI_DRAW: &[SubInfo] = &[
+    sub(0, "UPDATE", &[]),
+    sub(1, "CLEAN", &[]),
+    sub(2, "PIXEL", &[V, V, V]),
+    sub(3, "LINE", &[V, V, V, V, V]),
+    sub(4, "CIRCLE", &[V, V, V, V]),
+    sub(5, "TEXT", &[V, V, V, V]),
+    sub(6, "ICON", &[V, V, V, V, V]),
+    sub(7, "PICTURE", &[V, V, V, V]),
+    sub(8, "VALUE", &[V, V, V, V, V, V]),
+    sub(9, "FILLRECT", &[V, V, V, V, V]),
+    sub(10, "RECT", &[V, V, V, V, V]),
+    sub(11, "NOTIFICATION", &[V, V, V, V, V, V, V, V]),
+    sub(12, "QUESTION", &[V, V, V, V, V, V, V, V]),
+    sub(13, "KEYBOARD", &[V, V, V, V, V, V, V, V]),
+    sub(14, "BROWSE", &[V, V, V, V, V, V, V, V]),
+    sub(15, "VERTBAR", &[V, V, V, V, V, V, V, V]),
+    sub(16, "INVERSERECT", &[V, V, V, V]),
+    sub(17, "SELECT_FONT", &[V]),
+    sub(18, "TOPLINE", &[V]),
+    sub(19, "FILLWINDOW", &[V, V, V]),
+    sub(20, "SCROLL", &[V]),
+    sub(21, "DOTLINE", &[V, V, V, V, V, V, V]),
+    sub(22, "VIEW_VALUE", &[V, V, V, V, V, V]),
+    sub(23, "VIEW_UNIT", &[V, V, V, V, V, V, V, V]),
+    sub(24, "FILLCIRCLE", &[V, V, V, V]),
+    sub(25, "STORE", &[V]),
+    sub(26, "RESTORE", &[V]),
+    sub(27, "ICON_QUESTION", &[V, V, V, V, V]),
+    sub(28, "BMPFILE", &[V, V, V, V]),
+    sub(29, "POPUP", &[V]),
+    sub(31, "GRAPH_DRAW", &[V]),
+    sub(32, "TEXTBOX", &[V, V, V, V, V, V, V, V])
+];
+
+static SUB_MATH: &[SubInfo] = &[
+    sub(1, "EXP", &[V, V]),
+    sub(2, "MOD", &[V, V, V]),
+    sub(3, "FLOOR", &[V, V]),
+    sub(4, "CEIL", &[V, V]),
+    sub(5, "ROUND", &[V, V]),
+    sub(6, "ABS", &[V, V]),
+    sub(7, "NEGATE", &[V, V]),
+    sub(8, "SQRT", &[V, V]),
+    sub(9, "LOG", &[V, V]),
+    sub(10, "LN", &[V, V]),
+    sub(11, "SIN", &[V, V]),
+    sub(12, "COS", &[V, V]),
+    sub(13, "TAN", &[V, V]),
+    sub(14, "ASIN", &[V, V]),
+    sub(15, "ACOS", &[V, V]),
+    sub(16, "ATAN", &[V, V]),
+    sub(17, "MOD8", &[V, V, V]),
+    sub(18, "MOD16", &[V, V, V]),
+    sub(19, "MOD32", &[V, V, V]),
+    sub(20, "POW", &[V, V, V]),
+    sub(21, "TRUNC", &[V, V, V])
+];
+
+static SUB_SOUND: &[SubInfo] = &[
+    sub(0, "BREAK", &[]),
+    sub(1, "TONE", &[V, V, V]),
+    sub(2, "PLAY", &[V, V]),
+    sub(3, "REPEAT", &[V, V]),
+    sub(4, "SERVICE", &[])
+];
+
+static SUB_INPUT_DEVICE: &[SubInfo] = &[
+    sub(2, "GET_FORMAT", &[V, V, V, V, V, V]),
+    sub(3, "CAL_MINMAX", &[V, V, V, V]),
+    sub(4, "CAL_DEFAULT", &[V, V]),
+    sub(5, "GET_TYPEMODE", &[V, V, V, V]),
+    sub(6, "GET_SYMBOL", &[V, V, V, V]),
+    sub(7, "CAL_MIN", &[V, V, V]),
+    sub(8, "CAL_MAX", &[V, V, V]),
+    sub(9, "SETUP", &[V, V, V, V, V, V, V, V]),
+    sub(10, "CLR_ALL", &[V]),
+    sub(11, "GET_RAW", &[V, V, V]),
+    sub(12, "GET_CONNECTION", &[V, V, V]),
+    sub(13, "STOP_ALL", &[V]),
+    sub(21, "GET_NAME", &[V, V, V, V]),
+    sub(22, "GET_MODENAME", &[V, V, V, V, V]),
+    sub(24, "GET_FIGURES", &[V, V, V, V]),
+    sub(25, "GET_CHANGES", &[V, V, V]),
+    sub(26, "CLR_CHANGES", &[V, V]),
+    sub(27, "READY_PCT", &[V, V, V, V, N]),
+    sub(28, "READY_RAW", &[V, V, V, V, N]),
+    sub(29, "READY_SI", &[V, V, V, V, N]),
+    sub(30, "GET_MINMAX", &[V, V, V, V]),
+    sub(31, "GET_BUMPS", &[V, V, V])
+];
+
+static SUB_FILE: &[SubInfo] = &[
+    sub(0, "OPEN_APPEND", &[V, V]),
+    sub(1, "OPEN_READ", &[V, V, V]),
+    sub(2, "OPEN_WRITE", &[V, V]),
+    sub(3, "READ_VALUE", &[V, V, V]),
+    sub(4, "WRITE_VALUE", &[V, V, V, V, V]),
+    sub(5, "READ_TEXT", &[V, V, V, V]),
+    sub(6, "WRITE_TEXT", &[V, V, V]),
+    sub(7, "CLOSE", &[V]),
+    sub(8, "LOAD_IMAGE", &[V, V, V, V]),
+    sub(9, "GET_HANDLE", &[V, V, V]),
+    sub(10, "MAKE_FOLDER", &[V, V]),
+    sub(11, "GET_POOL", &[V, V, V]),
+    sub(12, "SET_LOG_SYNC_TIME", &[V, V]),
+    sub(13, "GET_FOLDERS", &[V, V]),
+    sub(14, "GET_LOG_SYNC_TIME", &[V, V]),
+    sub(15, "GET_SUBFOLDER_NAME", &[V, V, V, V]),
+    sub(16, "WRITE_LOG", &[V, V, V, V]),
+    sub(17, "CLOSE_LOG", &[V, V]),
+    sub(18, "GET_IMAGE", &[V, V, V, V]),
+    sub(19, "GET_ITEM", &[V, V, V]),
+    sub(20, "GET_CACHE_FILES", &[V]),
+    sub(21, "PUT_CACHE_FILE", &[V]),
+    sub(22, "GET_CACHE_FILE", &[V, V, V]),
+    sub(23, "DEL_CACHE_FILE", &[V]),
+    sub(24, "DEL_SUBFOLDER", &[V, V]),
+    sub(25, "GET_LOG_NAME", &[V, V]),
+    sub(27, "OPEN_LOG", &[V, V, V, V, V, V, V, V]),
+    sub(28, "READ_BYTES", &[V, V, V]),
+    sub(29, "WRITE_BYTES", &[V, V, V]),
+    sub(30, "REMOVE", &[V]),
+    sub(31, "MOVE", &[V, V])
+];
+
+static SUB_ARRAY: &[SubInfo] = &[
+    sub(0, "DELETE", &[V]),
+    sub(1, "CREATE8", &[V, V]),
+    sub(2, "CREATE16", &[V, V]),
+    sub(3, "CREATE32", &[V, V]),
+    sub(4, "CREATEF", &[V, V]),
+    sub(5, "RESIZE", &[V, V]),
+    sub(6, "FILL", &[V, V]),
+    sub(7, "COPY", &[V, V]),
+    sub(8, "INIT8", &[V, V, N]),
+    sub(9, "INIT16", &[V, V, N]),
+    sub(10, "INIT32", &[V, V, N]),
+    sub(11, "INITF", &[V, V, N]),
+    sub(12, "SIZE", &[V, V]),
+    sub(13, "READ_CONTENT", &[V, V, V, V, V]),
+    sub(14, "WRITE_CONTENT", &[V, V, V, V, V]),
+    sub(15, "READ_SIZE", &[V, V, V])
+];
+
+static SUB_FILENAME: &[SubInfo] = &[
+    sub(16, "EXIST", &[V, V]),
+    sub(17, "TOTALSIZE", &[V, V, V]),
+    sub(18, "SPLIT", &[V, V, V, V, V]),
+    sub(19, "MERGE", &[V, V, V, V, V]),
+    sub(20, "CHECK", &[V, V]),
+    sub(21, "PACK", &[V]),
+    sub(22, "UNPACK", &[V]),
+    sub(23, "GET_FOLDERNAME", &[V, V])
+];
+
+static SUB_COM_GET: &[SubInfo] = &[
+    sub(1, "GET_ON_OFF", &[V, V]),
+    sub(2, "GET_VISIBLE", &[V, V]),
+    sub(4, "GET_RESULT", &[V, V, V]),
+    sub(5, "GET_PIN", &[V, V, V, V]),
+    sub(8, "SEARCH_ITEMS", &[V, V]),
+    sub(9, "SEARCH_ITEM", &[V, V, V, V, V, V, V, V]),
+    sub(10, "FAVOUR_ITEMS", &[V, V]),
+    sub(11, "FAVOUR_ITEM", &[V, V, V, V, V, V, V]),
+    sub(12, "GET_ID", &[V, V, V]),
+    sub(13, "GET_BRICKNAME", &[V, V]),
+    sub(14, "GET_NETWORK", &[V, V, V, V, V]),
+    sub(15, "GET_PRESENT", &[V, V]),
+    sub(16, "GET_ENCRYPT", &[V, V, V]),
+    sub(17, "CONNEC_ITEMS", &[V, V]),
+    sub(18, "CONNEC_ITEM", &[V, V, V, V, V]),
+    sub(19, "GET_INCOMING", &[V, V, V, V]),
+    sub(20, "GET_MODE2", &[V, V])
+];
+
+static SUB_COM_SET: &[SubInfo] = &[
+    sub(1, "SET_ON_OFF", &[V, V]),
+    sub(2, "SET_VISIBLE", &[V, V]),
+    sub(3, "SET_SEARCH", &[V, V]),
+    sub(5, "SET_PIN", &[V, V, V]),
+    sub(6, "SET_PASSKEY", &[V, V]),
+    sub(7, "SET_CONNECTION", &[V, V, V]),
+    sub(8, "SET_BRICKNAME", &[V]),
+    sub(9, "SET_MOVEUP", &[V, V]),
+    sub(10, "SET_MOVEDOWN", &[V, V]),
+    sub(11, "SET_ENCRYPT", &[V, V, V]),
+    sub(12, "SET_SSID", &[V, V, V]),
+    sub(13, "SET_MODE2", &[V, V])
+];
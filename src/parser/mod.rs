@@ -3,14 +3,19 @@
 //! # Example
 //! Show information about reply
 //! ```
-//! let buf: Vec<u8> = vec![]; // Direct reply buffer
+//! use ev3_dc::parser::Reply;
+//!
+//! let buf: Vec<u8> = vec![0x03, 0x00, 0xAA, 0x00, 0x02]; // Direct reply buffer
 //! let rep = Reply::parse(&buf);
 //! println!("Length: {}, Id: {}, Error: {}, Memory: {:?}", rep.length(), rep.id(), rep.error(), rep.memory());
 //! ```
 
+use alloc::vec::Vec;
 use crate::DataType;
 
 /// Reply object
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Reply {
     length: u16,
     id: u16,
@@ -23,7 +28,7 @@ impl Reply {
     pub fn parse(packet: &[u8]) -> Self {
         let len = u16::from_le_bytes([packet[0], packet[1]]);
         let rid = u16::from_le_bytes([packet[2], packet[3]]);
-        let err = packet[4] == 0x20;
+        let err = packet[4] == 0x04; // DIRECT_REPLY_ERROR
         let mem = packet[5..].to_vec();
         Reply { length: len, id: rid, error: err, memory: mem }
     }
@@ -0,0 +1,169 @@
+//! Packet planner.
+//!
+//! [`plan`] split [`Operation`]s into as few [`Command`]s as packet length & memory limits allow.
+//! Each operation is kept whole inside one command, with its variables allocated in that command.
+//! # Example
+//! Read battery level & 2 tacho counts
+//! ```
+//! use ev3_dc::DataType::*;
+//! use ev3_dc::planner::{ plan, Operation };
+//!
+//! let mut ops = vec![];
+//! ops.push(Operation::new().code(vec![0x81, 0x12]).global(DATA8).clone()); // opUI_Read GET_LBATT
+//! for port in 0..2 {
+//!     ops.push(Operation::new().code(vec![0xB3, 0x00, port]).global(DATA32).clone()); // opOutput_Get_Count
+//! }
+//! let plan = plan(&ops).unwrap();
+//! assert_eq!(plan.commands.len(), 1);
+//! assert_eq!(plan.commands[0].bytecode, vec![0x81, 0x12, 0x60, 0xB3, 0x00, 0x00, 0x64, 0xB3, 0x00, 0x01, 0x68]);
+//! assert_eq!(plan.placements[2].command, 0);
+//! assert_eq!(plan.placements[2].variables[0].offset, 8);
+//! ```
+
+use alloc::{ vec, vec::Vec };
+use crate::{ Command, DataType, MemScope, ValError, Variable, GLOBAL_MAX, LOCAL_MAX };
+use crate::parser::Reply;
+use crate::utils::LEN_MAX;
+
+/// Part of [`Operation`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Piece {
+    /// Raw bytecode
+    Code(Vec<u8>),
+    /// Global variable, read from reply
+    Global(DataType),
+    /// Local variable
+    Local(DataType),
+}
+
+/// Bytecode with variables, placed whole in one command
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Operation {
+    pieces: Vec<Piece>,
+}
+
+impl Operation {
+    /// Create empty operation
+    pub fn new() -> Self {
+        Operation { pieces: vec![] }
+    }
+    /// Append bytecode
+    pub fn code(&mut self, bytecode: Vec<u8>) -> &mut Self {
+        self.pieces.push(Piece::Code(bytecode));
+        self
+    }
+    /// Append global variable parameter
+    pub fn global(&mut self, dtype: DataType) -> &mut Self {
+        self.pieces.push(Piece::Global(dtype));
+        self
+    }
+    /// Append local variable parameter
+    pub fn local(&mut self, dtype: DataType) -> &mut Self {
+        self.pieces.push(Piece::Local(dtype));
+        self
+    }
+    /// Get pieces
+    pub fn pieces(&self) -> &[Piece] {
+        &self.pieces
+    }
+    // Bytecode length of command after placing, or None if it doesn't fit
+    fn fits(&self, cmd: &Command) -> Option<usize> {
+        let mut global = cmd.reserved_bytes();
+        let mut local = cmd.local_bytes();
+        let mut length = cmd.bytecode.len();
+        for piece in &self.pieces {
+            let (used, max, scope, dtype) = match piece {
+                Piece::Code(code) => { length += code.len(); continue }
+                Piece::Global(dtype) => (&mut global, GLOBAL_MAX, MemScope::Global, dtype),
+                Piece::Local(dtype) => (&mut local, LOCAL_MAX, MemScope::Local, dtype)
+            };
+            let offset = used.next_multiple_of(dtype.align());
+            *used = offset.checked_add(dtype.size()).filter(|end| *end <= max as usize)?;
+            length += Variable { scope, offset: offset as u16, dtype: *dtype }.encode().ok()?.len();
+        }
+        (length <= LEN_MAX).then_some(length)
+    }
+    // Emit into command
+    fn place(&self, cmd: &mut Command) -> Result<Vec<Variable>, ValError> {
+        let mut vars = vec![];
+        for piece in &self.pieces {
+            let (dtype, global) = match piece {
+                Piece::Code(code) => { cmd.bytecode.extend(code); continue }
+                Piece::Global(dtype) => (*dtype, true),
+                Piece::Local(dtype) => (*dtype, false)
+            };
+            let var = cmd.allocate(dtype, global)?;
+            cmd.bytecode.extend(var);
+            vars.push(*cmd.variables().last().unwrap());
+        }
+        Ok(vars)
+    }
+}
+
+/// Where operation was placed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placement {
+    /// Index of command in [`Plan::commands`]
+    pub command: usize,
+    /// Variables in piece order
+    pub variables: Vec<Variable>,
+}
+
+/// Commands & placement of every operation
+pub struct Plan {
+    /// Planned commands. Ids start at 1
+    pub commands: Vec<Command>,
+    /// Placement of each operation, in operation order
+    pub placements: Vec<Placement>,
+}
+
+impl Plan {
+    /// Get global variable bytes of operation from replies in command order
+    pub fn read<'a>(&self, op: usize, replies: &'a [Reply]) -> Option<Vec<&'a [u8]>> {
+        let place = self.placements.get(op)?;
+        let memory = replies.get(place.command)?.memory();
+        place.variables.iter()
+            .filter(|var| var.scope == MemScope::Global)
+            .map(|var| var.read(memory))
+            .collect()
+    }
+}
+
+/// Split operations into commands. Error if single operation exceed packet length or memory
+/// # Example
+/// ```
+/// use ev3_dc::DataType::*;
+/// use ev3_dc::planner::{ plan, Operation };
+///
+/// let big = Operation::new().code(vec![0x01]).global(DATAN(600)).clone();
+/// let planned = plan(&[big.clone(), big.clone(), big]).unwrap();
+/// assert_eq!(planned.commands.len(), 3);
+/// assert_eq!(planned.placements[1].command, 1);
+///
+/// assert_eq!(plan(&[]).unwrap().commands.len(), 0);
+/// assert!(plan(&[Operation::new().code(vec![0x01; 1001]).clone()]).is_err());
+/// assert!(plan(&[Operation::new().local(DATAN(64)).clone()]).is_err());
+/// ```
+pub fn plan(ops: &[Operation]) -> Result<Plan, ValError> {
+    let mut commands: Vec<Command> = vec![];
+    let mut placements = vec![];
+    for op in ops {
+        if commands.last().and_then(|cmd| op.fits(cmd)).is_none() {
+            let cmd = Command::new();
+            if op.fits(&cmd).is_none() {
+                let length: usize = op.pieces.iter().map(|p| match p { Piece::Code(code) => code.len(), _ => 3 }).sum();
+                if length > LEN_MAX { return Err(ValError::InvalidRange(length as i32, 0, LEN_MAX as i32)) }
+            }
+            commands.push(cmd);
+        }
+        let index = commands.len() - 1;
+        let variables = op.place(&mut commands[index])?;
+        placements.push(Placement { command: index, variables });
+    }
+    for (i, cmd) in commands.iter_mut().enumerate() {
+        cmd.id = i as u16 + 1;
+    }
+    Ok(Plan { commands, placements })
+}
@@ -0,0 +1,301 @@
+//! RBF executable image.
+//!
+//! RBF image is a program that can be run by VM. It starts with image header (`LEGO` signature,
+//! image size, bytecode version, object count, global bytes), followed by object headers
+//! (instruction offset, owner object, trigger count, local bytes) and object bytecode.
+//! # Example
+//! Build image from assembled program
+//! ```
+//! use ev3_dc::asm::assemble;
+//! use ev3_dc::rbf::RbfBuilder;
+//!
+//! let program = assemble("
+//!     vmthread MAIN {
+//!         UI_WRITE(LED, 4)
+//!     }
+//! ").unwrap();
+//! let image = RbfBuilder::from_program(&program).build();
+//! assert_eq!(&image[0..4], b"LEGO");
+//! assert_eq!(image.len(), 16 + 12 + 4);
+//! // Upload with `system::begin_download` to "../prjs/demo/demo.rbf",
+//! // then load & start in user slot with opFile LOAD_IMAGE & opProgram_Start
+//! ```
+//!
+//! Inspect image and disassemble objects
+//! ```
+//! use ev3_dc::asm::assemble;
+//! use ev3_dc::rbf::{ RbfBuilder, RbfImage };
+//!
+//! let program = assemble("
+//!     vmthread MAIN {
+//!         DATA8 Count
+//!     Loop:
+//!         ADD8(Count, 1, Count)
+//!         JR(Loop)
+//!     }
+//! ").unwrap();
+//! let bytes = RbfBuilder::from_program(&program).build();
+//! let image = RbfImage::parse(&bytes).unwrap();
+//! assert_eq!(image.objects().len(), 1);
+//! assert_eq!(image.objects()[0].locals, 1);
+//! println!("{}", image.to_text());
+//! assert!(image.to_text().contains("0004  JR LC2(-8) -> 0000"));
+//! println!("{}", image.to_json());
+//! ```
+
+use alloc::{ format, string::{ String, ToString }, vec, vec::Vec };
+use core::fmt::Write;
+use crate::ValError;
+use crate::asm::{ ObjectKind, Program };
+use crate::opcode::{ instruction, Instruction };
+use crate::utils::json_string;
+
+/// Bytecode version written to image header (1.04)
+pub const BYTECODE_VERSION: u16 = 104;
+/// Image header size
+pub const IMAGE_HEADER: usize = 16;
+/// Object header size
+pub const OBJECT_HEADER: usize = 12;
+
+struct ObjectEntry {
+    owner: u16,
+    triggers: u16,
+    locals: u32,
+    bytecode: Vec<u8>,
+}
+
+/// RBF image builder
+#[derive(Default)]
+pub struct RbfBuilder {
+    globals: u32,
+    objects: Vec<ObjectEntry>,
+}
+
+impl RbfBuilder {
+    pub fn new() -> Self {
+        RbfBuilder::default()
+    }
+    /// Create builder with objects & global size of assembled program
+    pub fn from_program(program: &Program) -> Self {
+        let mut builder = RbfBuilder::new();
+        builder.globals(program.globals);
+        for object in &program.objects {
+            match object.kind {
+                ObjectKind::VMThread => builder.vmthread(object.bytecode.clone(), object.locals),
+                ObjectKind::SubCall => builder.subcall(object.bytecode.clone(), object.locals)
+            };
+        }
+        builder
+    }
+    /// Set global memory size
+    pub fn globals(&mut self, bytes: u32) -> &mut Self {
+        self.globals = bytes;
+        self
+    }
+    /// Add thread object. First object is started with program
+    pub fn vmthread(&mut self, bytecode: Vec<u8>, locals: u32) -> &mut Self {
+        self.objects.push(ObjectEntry { owner: 0, triggers: 0, locals, bytecode });
+        self
+    }
+    /// Add subcall object. Bytecode starts with parameter description
+    pub fn subcall(&mut self, bytecode: Vec<u8>, locals: u32) -> &mut Self {
+        self.objects.push(ObjectEntry { owner: 0, triggers: 1, locals, bytecode });
+        self
+    }
+    /// Add block object, owned by object id `owner`, started after `triggers` triggers
+    pub fn block(&mut self, owner: u16, triggers: u16, bytecode: Vec<u8>) -> &mut Self {
+        self.objects.push(ObjectEntry { owner, triggers, locals: 0, bytecode });
+        self
+    }
+    /// Generate RBF image bytes
+    pub fn build(&self) -> Vec<u8> {
+        let headers = IMAGE_HEADER + OBJECT_HEADER * self.objects.len();
+        let size = headers + self.objects.iter().map(|o| o.bytecode.len()).sum::<usize>();
+        let mut image: Vec<u8> = Vec::with_capacity(size);
+        image.extend(b"LEGO");
+        image.extend((size as u32).to_le_bytes());
+        image.extend(BYTECODE_VERSION.to_le_bytes());
+        image.extend((self.objects.len() as u16).to_le_bytes());
+        image.extend(self.globals.to_le_bytes());
+        let mut offset = headers;
+        for object in &self.objects {
+            image.extend((offset as u32).to_le_bytes());
+            image.extend(object.owner.to_le_bytes());
+            image.extend(object.triggers.to_le_bytes());
+            image.extend(object.locals.to_le_bytes());
+            offset += object.bytecode.len();
+        }
+        for object in &self.objects {
+            image.extend(&object.bytecode);
+        }
+        image
+    }
+}
+
+/// Object type of parsed image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectType {
+    /// Thread object (no owner, no trigger)
+    VMThread,
+    /// Subroutine object (no owner, 1 trigger)
+    SubCall,
+    /// Block object (owned by another object)
+    Block,
+}
+
+/// Parsed object of RBF image
+#[derive(Debug, Clone, PartialEq)]
+pub struct RbfObject<'a> {
+    /// Object id (index + 1)
+    pub id: u16,
+    /// Instruction offset in image
+    pub offset: u32,
+    /// Owner object id. 0 if none
+    pub owner: u16,
+    /// Trigger count
+    pub triggers: u16,
+    /// Local memory size in bytes
+    pub locals: u32,
+    /// Object bytecode, including subcall parameter description
+    pub bytecode: &'a [u8],
+}
+
+impl RbfObject<'_> {
+    /// Get object type
+    pub fn kind(&self) -> ObjectType {
+        match (self.owner, self.triggers) {
+            (0, 0) => ObjectType::VMThread,
+            (0, _) => ObjectType::SubCall,
+            _ => ObjectType::Block
+        }
+    }
+    /// Get subcall parameter names (e.g. `IN_8`). Empty for other object
+    pub fn parameters(&self) -> Vec<String> {
+        if self.kind() != ObjectType::SubCall { return vec![] }
+        let count = *self.bytecode.first().unwrap_or(&0) as usize;
+        self.bytecode.iter().skip(1).take(count).map(|flag| {
+            let dir = match flag & 0xC0 {
+                0x80 => "IN",
+                0x40 => "OUT",
+                _ => "IO"
+            };
+            let dtype = match flag & 0x07 {
+                0 => "8",
+                1 => "16",
+                2 => "32",
+                3 => "F",
+                _ => "S"
+            };
+            format!("{dir}_{dtype}")
+        }).collect()
+    }
+    /// Get instructions, excluding subcall parameter description
+    pub fn code(&self) -> &[u8] {
+        match self.kind() {
+            ObjectType::SubCall => {
+                let skip = (1 + *self.bytecode.first().unwrap_or(&0) as usize).min(self.bytecode.len());
+                &self.bytecode[skip..]
+            }
+            _ => self.bytecode
+        }
+    }
+    /// Disassemble object. Bytes after first undecodable instruction are returned as remainder
+    pub fn disassemble(&self) -> (Vec<Instruction<'_>>, &[u8]) {
+        let code = self.code();
+        let mut inst = vec![];
+        let mut pos = 0;
+        while let Ok(next) = instruction(code, pos) {
+            pos += next.length;
+            inst.push(next);
+        }
+        (inst, &code[pos..])
+    }
+}
+
+/// Parsed RBF image
+#[derive(Debug, Clone, PartialEq)]
+pub struct RbfImage<'a> {
+    size: u32,
+    version: u16,
+    globals: u32,
+    objects: Vec<RbfObject<'a>>,
+}
+
+impl<'a> RbfImage<'a> {
+    /// Parse RBF image
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, ValError> {
+        if bytes.len() < IMAGE_HEADER { return Err(ValError::Incomplete(bytes.len(), IMAGE_HEADER)) }
+        if &bytes[0..4] != b"LEGO" { return Err(ValError::InvalidImage("Missing LEGO signature".to_string())) }
+        let u16_at = |pos: usize| u16::from_le_bytes([bytes[pos], bytes[pos + 1]]);
+        let u32_at = |pos: usize| u32::from_le_bytes([bytes[pos], bytes[pos + 1], bytes[pos + 2], bytes[pos + 3]]);
+        let count = u16_at(10) as usize;
+        let headers = IMAGE_HEADER + OBJECT_HEADER * count;
+        if bytes.len() < headers { return Err(ValError::Incomplete(bytes.len(), headers)) }
+        let mut offsets: Vec<usize> = (0..count).map(|i| u32_at(IMAGE_HEADER + OBJECT_HEADER * i) as usize).collect();
+        offsets.push(bytes.len());
+        offsets.sort();
+        let mut objects = vec![];
+        for i in 0..count {
+            let head = IMAGE_HEADER + OBJECT_HEADER * i;
+            let offset = u32_at(head);
+            let start = offset as usize;
+            if start < headers || start > bytes.len() {
+                return Err(ValError::InvalidImage(format!("Object {} offset {} out of image", i + 1, offset)));
+            }
+            let end = *offsets.iter().find(|o| **o > start).unwrap_or(&bytes.len());
+            objects.push(RbfObject {
+                id: i as u16 + 1,
+                offset,
+                owner: u16_at(head + 4),
+                triggers: u16_at(head + 6),
+                locals: u32_at(head + 8),
+                bytecode: &bytes[start..end]
+            });
+        }
+        Ok(RbfImage { size: u32_at(4), version: u16_at(8), globals: u32_at(12), objects })
+    }
+    /// Get image size from header
+    pub fn size(&self) -> u32 { self.size }
+    /// Get bytecode version (e.g. 104 for 1.04)
+    pub fn version(&self) -> u16 { self.version }
+    /// Get global memory size
+    pub fn globals(&self) -> u32 { self.globals }
+    /// Get objects
+    pub fn objects(&self) -> &[RbfObject<'a>] { &self.objects }
+    /// Annotated disassembly listing
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        let _ = writeln!(text, "RBF image: {} byte(s), version {}.{:02}, {} object(s), {} global byte(s)",
+            self.size, self.version / 100, self.version % 100, self.objects.len(), self.globals);
+        for object in &self.objects {
+            let _ = write!(text, "\nObject {} {:?} at {:#06X}, owner {}, triggers {}, {} local byte(s)",
+                object.id, object.kind(), object.offset, object.owner, object.triggers, object.locals);
+            let params = object.parameters();
+            if !params.is_empty() { let _ = write!(text, ", parameters: {}", params.join(", ")); }
+            text.push('\n');
+            let (code, rest) = object.disassemble();
+            for inst in code { let _ = writeln!(text, "  {inst}"); }
+            if !rest.is_empty() { let _ = writeln!(text, "  ; undecoded {:02X?}", rest); }
+        }
+        text
+    }
+    /// Disassembly as JSON
+    pub fn to_json(&self) -> String {
+        let objects: Vec<String> = self.objects.iter().map(|object| {
+            let (code, rest) = object.disassemble();
+            let code: Vec<String> = code.iter().map(|inst| {
+                let params: Vec<String> = inst.params.iter().map(|p| json_string(&format!("{p:?}"))).collect();
+                format!("{{\"offset\":{},\"op\":{},\"sub\":{},\"params\":[{}],\"target\":{}}}",
+                    inst.offset, json_string(inst.op.name),
+                    inst.sub.map(|s| json_string(s.name)).unwrap_or("null".to_string()),
+                    params.join(","), inst.target.map(|t| t.to_string()).unwrap_or("null".to_string()))
+            }).collect();
+            let params: Vec<String> = object.parameters().iter().map(|p| json_string(p)).collect();
+            format!("{{\"id\":{},\"kind\":{},\"offset\":{},\"owner\":{},\"triggers\":{},\"locals\":{},\"parameters\":[{}],\"code\":[{}],\"undecoded\":{:?}}}",
+                object.id, json_string(&format!("{:?}", object.kind())), object.offset, object.owner, object.triggers,
+                object.locals, params.join(","), code.join(","), rest)
+        }).collect();
+        format!("{{\"size\":{},\"version\":{},\"globals\":{},\"objects\":[{}]}}",
+            self.size, self.version, self.globals, objects.join(","))
+    }
+}
@@ -0,0 +1,246 @@
+//! Interactive shell.
+//!
+//! [`Shell`] compile line of symbolic instructions into direct command, send it and decode reply memory.
+//! Instructions are separated by `;`, words by spaces or commas:
+//!  - OpCode & sub-command names of [`crate::opcode::catalogue`], case-insensitive
+//!  - Integers (`50`, `-1`, `0x1F`) and floats (`1.5`, `2F`)
+//!  - Motor ports `A` - `D` as bit mask, e.g. `AB` = 3
+//!  - Strings `'text'`
+//!  - Reply variables `?8`, `?16`, `?32`, `?F`, `?S<length>` & `?N<length>`, allocated in global memory
+//!
+//! # Example
+//! Read battery level from simulated brick
+//! ```
+//! use ev3_dc::repl::Shell;
+//! use ev3_dc::transport::{ Client, MockBrick };
+//!
+//! let mut brick = MockBrick::new();
+//! brick.memory(&[0x50]);
+//! let mut shell = Shell::new(Client::new(brick).0);
+//! let out = shell.eval("ui_read get_lbatt ?8").unwrap();
+//! assert_eq!(out.to_string(), "SENT: 08 00 01 00 00 01 00 81 12 60\nMEMORY: 50\n  0x000 DATA8    = 80");
+//! ```
+
+use alloc::{ format, string::{ String, ToString }, vec, vec::Vec };
+use core::fmt;
+use crate::{ encode, Command, DataType, Encoding::*, Error, ValError, Variable };
+use crate::asm::parse_int;
+use crate::opcode::{ by_name, catalogue, Param, Params };
+use crate::parser::Reply;
+use crate::sampler::Value;
+use crate::transport::{ Client, Transport };
+use crate::utils::auto_const;
+
+// Split instruction into words with byte offset. Quoted strings are kept whole
+fn words(text: &str, base: usize) -> Vec<(usize, &str)> {
+    let mut words = vec![];
+    let mut start = None;
+    let mut quoted = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            '\'' => { quoted = !quoted; start.get_or_insert(i); }
+            c if !quoted && (c.is_whitespace() || c == ',') => {
+                if let Some(s) = start.take() { words.push((base + s, &text[s..i])); }
+            }
+            _ => { start.get_or_insert(i); }
+        }
+    }
+    if let Some(s) = start { words.push((base + s, &text[s..])); }
+    words
+}
+
+// Split line into instructions with byte offset
+fn instructions(line: &str) -> Vec<(usize, &str)> {
+    let mut parts = vec![];
+    let mut start = 0;
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '\'' => quoted = !quoted,
+            ';' if !quoted => { parts.push((start, &line[start..i])); start = i + 1; }
+            _ => {}
+        }
+    }
+    parts.push((start, &line[start..]));
+    parts
+}
+
+// Reply variable type, e.g. `?32`
+fn reply_type(word: &str) -> Option<DataType> {
+    let spec = word.strip_prefix('?')?.to_ascii_uppercase();
+    let length = |rest: &str| rest.parse::<usize>().ok().filter(|n| *n > 0);
+    Some(match spec.as_str() {
+        "8" => DataType::DATA8,
+        "16" => DataType::DATA16,
+        "32" => DataType::DATA32,
+        "F" => DataType::DATAF,
+        _ => match spec.split_at_checked(1)? {
+            ("S", rest) => DataType::DATAS(length(rest)?),
+            ("N", rest) => DataType::DATAN(length(rest)?),
+            _ => return None
+        }
+    })
+}
+
+// Encode single parameter word
+fn param(pos: usize, word: &str, cmd: &mut Command) -> Result<Vec<u8>, ValError> {
+    if let Some(text) = word.strip_prefix('\'').and_then(|w| w.strip_suffix('\'')) {
+        return encode(LCS(text));
+    }
+    if word.starts_with('?') {
+        let dtype = reply_type(word).ok_or(ValError::Parse(pos, format!("Invalid reply variable {word}")))?;
+        return cmd.allocate(dtype, true);
+    }
+    if let Some(val) = parse_int(word) { return auto_const(val) }
+    let float = word.strip_suffix(['F', 'f']).unwrap_or(word);
+    if let Ok(val) = float.parse::<f32>() && (float.contains('.') || float.len() < word.len()) {
+        return encode(LCF(val));
+    }
+    if word.chars().all(|c| matches!(c.to_ascii_uppercase(), 'A'..='D')) {
+        let mask = word.to_ascii_uppercase().bytes().fold(0, |mask, c| mask | 1 << (c - b'A'));
+        return auto_const(mask);
+    }
+    Err(ValError::Parse(pos, format!("Invalid parameter {word}")))
+}
+
+/// Compile line of symbolic instructions. Reply variables are allocated in command
+/// # Example
+/// ```
+/// use ev3_dc::Command;
+/// use ev3_dc::repl::compile;
+///
+/// let mut cmd = Command::new();
+/// assert_eq!(compile("ui_write led 4", &mut cmd).unwrap(), vec![0x82, 0x1B, 0x04]);
+/// assert_eq!(compile("output_speed 0 A 50; output_start 0 A", &mut cmd).unwrap(),
+///     vec![0xA5, 0x00, 0x01, 0x81, 0x32, 0xA6, 0x00, 0x01]);
+/// assert_eq!(compile("output_get_count 0, 1, ?32", &mut cmd).unwrap(), vec![0xB3, 0x00, 0x01, 0x60]);
+/// assert_eq!(cmd.reserved_bytes(), 4);
+/// assert!(compile("output_speed 0 A", &mut cmd).is_err());
+/// assert!(compile("ui_write blink 4", &mut cmd).is_err());
+/// ```
+pub fn compile(line: &str, cmd: &mut Command) -> Result<Vec<u8>, ValError> {
+    let mut bytes = vec![];
+    for (base, text) in instructions(line) {
+        let words = words(text, base);
+        let mut words = words.iter();
+        let Some((pos, name)) = words.next() else { continue };
+        let op = by_name(name).ok_or(ValError::Parse(*pos, format!("Unknown OpCode {name}")))?;
+        bytes.push(op.code);
+        let layout = match op.params {
+            Params::Fixed(layout) => layout,
+            Params::Sub(_) => {
+                let (pos, word) = words.next().ok_or(ValError::Parse(pos + name.len(), "Expect sub-command".to_string()))?;
+                let sub = op.sub_by_name(word).or(parse_int(word).and_then(|c| op.sub(c as u8)))
+                    .ok_or(ValError::Parse(*pos, format!("Unknown sub-command {word}")))?;
+                bytes.extend(auto_const(sub.code as i32)?);
+                sub.params
+            }
+        };
+        for kind in layout {
+            match kind {
+                Param::Value | Param::Label => {
+                    let (pos, word) = words.next().ok_or(ValError::Parse(base + text.len(), format!("Missing parameter of {}", op.name)))?;
+                    bytes.extend(param(*pos, word, cmd)?);
+                }
+                Param::Count => {
+                    let rest: Vec<&(usize, &str)> = words.by_ref().collect();
+                    bytes.extend(auto_const(rest.len() as i32)?);
+                    for (pos, word) in rest { bytes.extend(param(*pos, word, cmd)?); }
+                }
+            }
+        }
+        if let Some((pos, _)) = words.next() { return Err(ValError::Parse(*pos, format!("Too many parameters for {}", op.name))) }
+    }
+    Ok(bytes)
+}
+
+/// Complete word ending at cursor. Return start of word and lowercase OpCode or sub-command names
+/// # Example
+/// ```
+/// use ev3_dc::repl::complete;
+///
+/// assert_eq!(complete("ui_wr", 5), (0, vec!["ui_write".to_string()]));
+/// assert!(complete("ui_write le", 11).1.contains(&"led".to_string()));
+/// assert_eq!(complete("ui_write led 4; sound_t", 23).1, vec!["sound_test".to_string()]);
+/// assert!(complete("output_speed 0 ", 15).1.is_empty());
+/// ```
+pub fn complete(line: &str, cursor: usize) -> (usize, Vec<String>) {
+    let line = &line[..cursor];
+    let (base, text) = *instructions(line).last().unwrap();
+    let mut words = words(text, base);
+    if line.ends_with(|c: char| c.is_whitespace() || c == ',' || c == ';') { words.push((cursor, "")); }
+    let (start, prefix) = words.last().copied().unwrap_or((cursor, ""));
+    let prefix = prefix.to_ascii_lowercase();
+    let names: Vec<String> = match words.len() {
+        0 | 1 => catalogue().iter().map(|op| op.name.to_ascii_lowercase()).collect(),
+        2 => match by_name(words[0].1).map(|op| op.params) {
+            Some(Params::Sub(subs)) => subs.iter().map(|sub| sub.name.to_ascii_lowercase()).collect(),
+            _ => vec![]
+        },
+        _ => vec![]
+    };
+    (start, names.into_iter().filter(|name| name.starts_with(&prefix)).collect())
+}
+
+/// Result of evaluated line
+#[derive(Debug, Clone)]
+pub struct Output {
+    /// Sent packet
+    pub packet: Vec<u8>,
+    /// Parsed reply
+    pub reply: Reply,
+    /// Reply variables with decoded value, in allocation order
+    pub values: Vec<(Variable, Value)>,
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02X}")).collect::<Vec<_>>().join(" ")
+}
+
+impl fmt::Display for Output {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SENT: {}\nMEMORY: {}", hex(&self.packet), hex(self.reply.memory()))?;
+        for (var, value) in &self.values {
+            write!(f, "\n  {:#05X} {:<8} = ", var.offset, var.dtype.to_string())?;
+            match (var.dtype, value) {
+                (_, Value::Int(val)) => write!(f, "{val}")?,
+                (_, Value::Float(val)) => write!(f, "{val}")?,
+                (DataType::DATAS(_), Value::Bytes(bytes)) => {
+                    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+                    write!(f, "{:?}", String::from_utf8_lossy(&bytes[..end]))?
+                }
+                (_, Value::Bytes(bytes)) => write!(f, "{}", hex(bytes))?
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Interactive shell session. Each line is sent as one direct command with new id
+pub struct Shell<T: Transport> {
+    client: Client<T>,
+    id: u16,
+}
+
+impl<T: Transport> Shell<T> {
+    /// Create shell over client
+    pub fn new(client: Client<T>) -> Self {
+        Shell { client, id: 0 }
+    }
+    /// Get underlying client
+    pub fn client(&mut self) -> &mut Client<T> {
+        &mut self.client
+    }
+    /// Compile & send line, then decode reply variables
+    pub fn eval(&mut self, line: &str) -> Result<Output, Error> {
+        let mut cmd = Command::new();
+        cmd.bytecode = compile(line, &mut cmd)?;
+        self.id = self.id.wrapping_add(1);
+        cmd.id = self.id;
+        let reply = self.client.direct(&cmd)?;
+        let values = cmd.variables().iter()
+            .map(|var| (*var, Value::from_bytes(var.read(reply.memory()).unwrap_or(&[]), var.dtype)))
+            .collect();
+        Ok(Output { packet: cmd.gen_bytes(), reply, values })
+    }
+}
@@ -0,0 +1,211 @@
+//! Periodic multi-sensor sampler.
+//!
+//! [`Sampler`] pack reads into as few [`Command`]s as global reply memory & packet length allow
+//! (using [`crate::planner`]),
+//! fire them on interval and return timestamped [`Sample`]s.
+//! # Example
+//! ```no_run
+//! use std::time::Duration;
+//! use ev3_dc::sampler::{ Read, Sampler };
+//! use ev3_dc::transport::{ Client, StreamTransport };
+//!
+//! let (client, _) = Client::new(StreamTransport::open("/dev/rfcomm0").unwrap());
+//! let reads = vec![
+//!     Read::Sensor { layer: 0, port: 0, mode: 0 },
+//!     Read::Sensor { layer: 0, port: 1, mode: 0 },
+//!     Read::Tacho { layer: 0, port: 0 },
+//!     Read::Tacho { layer: 0, port: 1 },
+//! ];
+//! let sampler = Sampler::new(&reads, Duration::from_millis(50)).unwrap();
+//! assert_eq!(sampler.commands().len(), 1); // One round trip per sample
+//! for sample in sampler.spawn(client) {
+//!     println!("{:?}", sample.unwrap().values);
+//! }
+//! ```
+
+use alloc::vec::Vec;
+use std::sync::mpsc::{ channel, Receiver };
+use std::thread;
+use std::time::{ Duration, Instant };
+use crate::{ encode, Command, DataType, Encoding::*, Error, ValError, Variable };
+use crate::error::ReplyError;
+use crate::parser::Reply;
+use crate::transport::{ Client, Transport };
+use crate::planner::{ plan, Operation };
+use crate::utils::ChainByte;
+
+/// Single read of sampler
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Read {
+    /// Sensor value in SI unit (opInput_ReadSI). Mode -1 keep current mode
+    Sensor { layer: u8, port: u8, mode: i8 },
+    /// Motor tacho count (opOutput_Get_Count). Port is 0 - 3
+    Tacho { layer: u8, port: u8 },
+    /// Custom bytecode, followed by global variable of type
+    Custom(Vec<u8>, DataType),
+}
+
+impl Read {
+    /// Get type of read value
+    pub fn dtype(&self) -> DataType {
+        match self {
+            Read::Sensor { .. } => DataType::DATAF,
+            Read::Tacho { .. } => DataType::DATA32,
+            Read::Custom(_, dtype) => *dtype
+        }
+    }
+    // Bytecode before result variable
+    fn prefix(&self) -> Result<Vec<u8>, ValError> {
+        let mut byte = ChainByte::new();
+        match self {
+            Read::Sensor { layer, port, mode } => {
+                if *layer > 3 { return Err(ValError::InvalidRange(*layer as i32, 0, 3)) }
+                if *port > 31 { return Err(ValError::InvalidRange(*port as i32, 0, 31)) }
+                byte.push(0x9D) // opInput_ReadSI
+                    .add(encode(LC0(*layer as i8))?)
+                    .add(encode(LC0(*port as i8))?)
+                    .add(encode(LC0(0))?) // Keep type
+                    .add(encode(LC1(*mode))?);
+            }
+            Read::Tacho { layer, port } => {
+                if *layer > 3 { return Err(ValError::InvalidRange(*layer as i32, 0, 3)) }
+                if *port > 3 { return Err(ValError::InvalidRange(*port as i32, 0, 3)) }
+                byte.push(0xB3) // opOutput_Get_Count
+                    .add(encode(LC0(*layer as i8))?)
+                    .add(encode(LC0(*port as i8))?);
+            }
+            Read::Custom(code, _) => { byte.add(code.clone()); }
+        }
+        Ok(byte.bytes)
+    }
+}
+
+/// Typed value of read
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Value {
+    Int(i32),
+    Float(f32),
+    Bytes(Vec<u8>),
+}
+
+impl Value {
+    /// Decode value from variable bytes
+    pub fn from_bytes(bytes: &[u8], dtype: DataType) -> Self {
+        match (dtype, bytes.len()) {
+            (DataType::DATA8, 1) => Value::Int(bytes[0] as i8 as i32),
+            (DataType::DATA16, 2) => Value::Int(i16::from_le_bytes([bytes[0], bytes[1]]) as i32),
+            (DataType::DATA32, 4) => Value::Int(i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+            (DataType::DATAF, 4) => Value::Float(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+            _ => Value::Bytes(bytes.to_vec())
+        }
+    }
+    /// Get value as float
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            Value::Int(val) => Some(*val as f32),
+            Value::Float(val) => Some(*val),
+            Value::Bytes(_) => None
+        }
+    }
+}
+
+/// Timestamped values in read order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    /// Time last reply arrived
+    pub time: Instant,
+    /// Values in read order
+    pub values: Vec<Value>,
+}
+
+/// Batched periodic reader
+pub struct Sampler {
+    commands: Vec<Command>,
+    // (command index, variable) of each read
+    slots: Vec<(usize, Variable)>,
+    interval: Duration,
+}
+
+impl Sampler {
+    /// Pack reads into commands. Command ids start at 1
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use ev3_dc::DataType;
+    /// use ev3_dc::sampler::{ Read, Sampler };
+    ///
+    /// let reads = vec![Read::Custom(vec![0x81, 0x12], DataType::DATAN(600)); 3]; // 3 x 600 bytes
+    /// let sampler = Sampler::new(&reads, Duration::from_secs(1)).unwrap();
+    /// assert_eq!(sampler.commands().len(), 3);
+    /// assert_eq!(sampler.commands()[2].id, 3);
+    ///
+    /// let reads = vec![Read::Tacho { layer: 0, port: 0 }; 200]; // 800 bytes, but too long for one packet
+    /// let sampler = Sampler::new(&reads, Duration::from_secs(1)).unwrap();
+    /// assert_eq!(sampler.commands().len(), 2);
+    /// ```
+    pub fn new(reads: &[Read], interval: Duration) -> Result<Self, ValError> {
+        let ops = reads.iter()
+            .map(|read| Ok(Operation::new().code(read.prefix()?).global(read.dtype()).clone()))
+            .collect::<Result<Vec<Operation>, ValError>>()?;
+        let plan = plan(&ops)?;
+        let slots = plan.placements.into_iter().map(|place| (place.command, place.variables[0])).collect();
+        Ok(Sampler { commands: plan.commands, slots, interval })
+    }
+    /// Get packed commands
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+    /// Get sample interval
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+    /// Decode values from replies in command order
+    pub fn values(&self, replies: &[Reply]) -> Result<Vec<Value>, ReplyError> {
+        self.slots.iter().map(|(i, var)| {
+            let memory = replies.get(*i).map(|rep| rep.memory()).unwrap_or(&[]);
+            let bytes = var.read(memory)
+                .ok_or(ReplyError::Truncated(memory.len(), var.offset as usize + var.dtype.size()))?;
+            Ok(Value::from_bytes(bytes, var.dtype))
+        }).collect()
+    }
+    /// Send every command once and return sample
+    pub fn sample<T: Transport>(&self, client: &mut Client<T>) -> Result<Sample, Error> {
+        let replies = self.commands.iter()
+            .map(|cmd| client.direct(cmd))
+            .collect::<Result<Vec<Reply>, Error>>()?;
+        Ok(Sample { time: Instant::now(), values: self.values(&replies)? })
+    }
+    /// Sample on interval, blocking between samples
+    pub fn iter<'a, T: Transport>(&'a self, client: &'a mut Client<T>) -> Samples<'a, T> {
+        Samples { sampler: self, client, next: Instant::now() }
+    }
+    /// Sample on interval in background thread. Stop when receiver is dropped or after first error
+    pub fn spawn<T: Transport + Send + 'static>(self, mut client: Client<T>) -> Receiver<Result<Sample, Error>> {
+        let (tx, rx) = channel();
+        thread::spawn(move || {
+            for sample in self.iter(&mut client) {
+                let failed = sample.is_err();
+                if tx.send(sample).is_err() || failed { break }
+            }
+        });
+        rx
+    }
+}
+
+/// Iterator of samples from [`Sampler::iter`]
+pub struct Samples<'a, T: Transport> {
+    sampler: &'a Sampler,
+    client: &'a mut Client<T>,
+    next: Instant,
+}
+
+impl<T: Transport> Iterator for Samples<'_, T> {
+    type Item = Result<Sample, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        thread::sleep(self.next.saturating_duration_since(Instant::now()));
+        self.next = Instant::now().max(self.next + self.sampler.interval);
+        Some(self.sampler.sample(self.client))
+    }
+}
@@ -0,0 +1,197 @@
+//! System command packet generation & reply parsing.
+//!
+//! System commands handle file transfer, mailbox & other brick functions outside the VM.
+//! # Example
+//! Download (host to brick) file in chunks
+//! ```
+//! use ev3_dc::system::{ begin_download, continue_download, SystemReply };
+//!
+//! let data = vec![0_u8; 2500];
+//! let begin = begin_download("../prjs/demo/demo.rbf", data.len() as u32).unwrap();
+//! println!("SENT: {:02X?}", begin.gen_bytes());
+//! // Read reply and get file handle
+//! let rep = SystemReply::parse(&[0x07, 0x00, 0xAA, 0x00, 0x03, 0x92, 0x00, 0x01]).unwrap();
+//! let handle = rep.payload()[0];
+//! for chunk in data.chunks(1000) {
+//!     println!("SENT: {:02X?}", continue_download(handle, chunk).gen_bytes());
+//! }
+//! ```
+
+use alloc::{ string::ToString, vec, vec::Vec };
+use crate::ValError;
+use crate::error::{ ReplyError, SystemStatus };
+
+/// BEGIN_DOWNLOAD: Start file download (host to brick)
+pub const BEGIN_DOWNLOAD: u8 = 0x92;
+/// CONTINUE_DOWNLOAD: Download file chunk
+pub const CONTINUE_DOWNLOAD: u8 = 0x93;
+/// BEGIN_UPLOAD: Start file upload (brick to host)
+pub const BEGIN_UPLOAD: u8 = 0x94;
+/// CONTINUE_UPLOAD: Upload file chunk
+pub const CONTINUE_UPLOAD: u8 = 0x95;
+/// CLOSE_FILEHANDLE: Close file handle
+pub const CLOSE_FILEHANDLE: u8 = 0x98;
+/// LIST_FILES: Start listing folder
+pub const LIST_FILES: u8 = 0x99;
+/// CONTINUE_LIST_FILES: Continue listing folder
+pub const CONTINUE_LIST_FILES: u8 = 0x9A;
+/// CREATE_DIR: Create folder
+pub const CREATE_DIR: u8 = 0x9B;
+/// DELETE_FILE: Delete file or empty folder
+pub const DELETE_FILE: u8 = 0x9C;
+/// WRITEMAILBOX: Write mailbox message
+pub const WRITEMAILBOX: u8 = 0x9E;
+
+/// Maximum file chunk size of single system command
+pub const CHUNK_MAX: usize = 1000;
+
+/// System Command object
+pub struct SystemCommand {
+    /// Command ID
+    pub id: u16,
+    /// Reply to system command
+    pub reply: bool,
+    /// System command byte
+    pub command: u8,
+    /// Bytes after command byte
+    pub payload: Vec<u8>,
+}
+
+impl SystemCommand {
+    /// Create system command with reply
+    pub fn new(command: u8, payload: Vec<u8>) -> Self {
+        SystemCommand { id: 170, reply: true, command, payload }
+    }
+    /// Generate system command bytecode
+    pub fn gen_bytes(&self) -> Vec<u8> {
+        let mut packet: Vec<u8> = vec![];
+        packet.extend(((4 + self.payload.len()) as u16).to_le_bytes());
+        packet.extend(self.id.to_le_bytes());
+        packet.push(match self.reply {
+            true => 0x01,
+            false => 0x81,
+        });
+        packet.push(self.command);
+        packet.extend(&self.payload);
+        packet
+    }
+}
+
+/// System reply object
+pub struct SystemReply {
+    length: u16,
+    id: u16,
+    command: u8,
+    status: SystemStatus,
+    error: bool,
+    payload: Vec<u8>,
+}
+
+impl SystemReply {
+    /// Parse system reply packet
+    pub fn parse(packet: &[u8]) -> Result<Self, ReplyError> {
+        if packet.len() < 7 { return Err(ReplyError::Truncated(packet.len(), 7)) }
+        let length = u16::from_le_bytes([packet[0], packet[1]]);
+        let end = (2 + length as usize).min(packet.len());
+        let error = match packet[4] {
+            0x03 => false,
+            0x05 => true,
+            other => return Err(ReplyError::UnknownType(other))
+        };
+        Ok(SystemReply {
+            length,
+            id: u16::from_le_bytes([packet[2], packet[3]]),
+            command: packet[5],
+            status: SystemStatus::from_code(packet[6]),
+            error,
+            payload: packet[7..end.max(7)].to_vec()
+        })
+    }
+    /// Get reply's length excluding first 2 bytes
+    pub fn length(&self) -> u16 { self.length }
+    /// Get reply's id. Command and reply match up if they have same id
+    pub fn id(&self) -> u16 { self.id }
+    /// Get system command byte this reply answers
+    pub fn command(&self) -> u8 { self.command }
+    /// Get reply status
+    pub fn status(&self) -> SystemStatus { self.status }
+    /// Check reply's error (SYSTEM_REPLY_ERROR)
+    pub fn error(&self) -> bool { self.error }
+    /// Get bytes after status
+    pub fn payload(&self) -> &[u8] { &self.payload }
+    /// Return status as error unless it is [`SystemStatus::Success`].
+    /// [`SystemStatus::EndOfFile`] is success for last chunk of transfer
+    pub fn check(&self) -> Result<&Self, SystemStatus> {
+        match self.status {
+            SystemStatus::Success | SystemStatus::EndOfFile => Ok(self),
+            status => Err(status)
+        }
+    }
+}
+
+// Zero-terminated path
+fn path_bytes(path: &str) -> Result<Vec<u8>, ValError> {
+    if path.contains('\0') { return Err(ValError::InvalidString(path.to_string())) }
+    let mut bytes = path.as_bytes().to_vec();
+    bytes.push(0);
+    Ok(bytes)
+}
+
+/// Start file download (host to brick). Reply payload is file handle
+pub fn begin_download(path: &str, size: u32) -> Result<SystemCommand, ValError> {
+    let mut payload = size.to_le_bytes().to_vec();
+    payload.extend(path_bytes(path)?);
+    Ok(SystemCommand::new(BEGIN_DOWNLOAD, payload))
+}
+
+/// Download file chunk. Chunk should be at most [`CHUNK_MAX`] bytes
+pub fn continue_download(handle: u8, chunk: &[u8]) -> SystemCommand {
+    let mut payload = vec![handle];
+    payload.extend(chunk);
+    SystemCommand::new(CONTINUE_DOWNLOAD, payload)
+}
+
+/// Start file upload (brick to host), reading at most `max` bytes. \
+/// Reply payload is file size (4 bytes), handle and data
+pub fn begin_upload(path: &str, max: u16) -> Result<SystemCommand, ValError> {
+    let mut payload = max.to_le_bytes().to_vec();
+    payload.extend(path_bytes(path)?);
+    Ok(SystemCommand::new(BEGIN_UPLOAD, payload))
+}
+
+/// Continue file upload. Reply payload is handle and data
+pub fn continue_upload(handle: u8, max: u16) -> SystemCommand {
+    let mut payload = vec![handle];
+    payload.extend(max.to_le_bytes());
+    SystemCommand::new(CONTINUE_UPLOAD, payload)
+}
+
+/// Close file handle
+pub fn close_handle(handle: u8) -> SystemCommand {
+    SystemCommand::new(CLOSE_FILEHANDLE, vec![handle])
+}
+
+/// List folder, reading at most `max` bytes. \
+/// Reply payload is list size (4 bytes), handle and list text
+pub fn list_files(path: &str, max: u16) -> Result<SystemCommand, ValError> {
+    let mut payload = max.to_le_bytes().to_vec();
+    payload.extend(path_bytes(path)?);
+    Ok(SystemCommand::new(LIST_FILES, payload))
+}
+
+/// Continue listing folder
+pub fn continue_list(handle: u8, max: u16) -> SystemCommand {
+    let mut payload = vec![handle];
+    payload.extend(max.to_le_bytes());
+    SystemCommand::new(CONTINUE_LIST_FILES, payload)
+}
+
+/// Create folder
+pub fn create_dir(path: &str) -> Result<SystemCommand, ValError> {
+    Ok(SystemCommand::new(CREATE_DIR, path_bytes(path)?))
+}
+
+/// Delete file or empty folder
+pub fn delete_file(path: &str) -> Result<SystemCommand, ValError> {
+    Ok(SystemCommand::new(DELETE_FILE, path_bytes(path)?))
+}
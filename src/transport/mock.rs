@@ -0,0 +1,78 @@
+//! Simulated brick
+
+use alloc::{ vec, vec::Vec };
+use std::collections::VecDeque;
+use std::io;
+use std::time::Duration;
+use crate::system::{ BEGIN_UPLOAD, LIST_FILES };
+use super::Transport;
+
+/// Simulated brick. Direct commands are answered with reply memory of requested global size,
+/// system commands with SUCCESS. File upload & listing are empty (END_OF_FILE with size 0).
+/// Commands without reply are only recorded
+/// # Example
+/// ```
+/// use ev3_dc::{ Command, DataType::* };
+/// use ev3_dc::transport::{ Client, MockBrick };
+///
+/// let mut brick = MockBrick::new();
+/// brick.memory(&[0x2A]);
+/// let (mut client, _) = Client::new(brick);
+/// let mut cmd = Command::new();
+/// let var = cmd.allocate(DATA8, true).unwrap();
+/// cmd.bytecode = [vec![0x81, 0x12], var].concat(); // opUI_Read GET_LBATT
+/// assert_eq!(client.direct(&cmd).unwrap().memory(), &[0x2A]);
+/// assert_eq!(client.transport().sent()[0], cmd.gen_bytes());
+/// assert_eq!(client.list("../prjs/").unwrap(), "");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockBrick {
+    memory: Vec<u8>,
+    sent: Vec<Vec<u8>>,
+    replies: VecDeque<Vec<u8>>,
+}
+
+impl MockBrick {
+    /// Create brick replying zeroed memory
+    pub fn new() -> Self {
+        MockBrick::default()
+    }
+    /// Set reply memory content. Padded with zero or cut to requested size
+    pub fn memory(&mut self, bytes: &[u8]) -> &mut Self {
+        self.memory = bytes.to_vec();
+        self
+    }
+    /// Get every sent packet
+    pub fn sent(&self) -> &[Vec<u8>] {
+        &self.sent
+    }
+}
+
+impl Transport for MockBrick {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        if packet.len() < 5 { return Err(io::Error::new(io::ErrorKind::InvalidData, "Truncated packet")) }
+        self.sent.push(packet.to_vec());
+        let mut body = match packet[4] {
+            0x00 => {
+                let alloc = packet.get(5..7).map_or(0, |a| u16::from_le_bytes([a[0], a[1]]));
+                let mut memory = self.memory.clone();
+                memory.resize((alloc & 0x3FF) as usize, 0);
+                [vec![0x02], memory].concat()
+            }
+            0x01 => match packet.get(5).copied().unwrap_or(0) {
+                // Size 0 & handle 0
+                cmd @ (BEGIN_UPLOAD | LIST_FILES) => vec![0x03, cmd, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00],
+                cmd => vec![0x03, cmd, 0x00]
+            },
+            _ => return Ok(())
+        };
+        let mut reply = ((body.len() + 2) as u16).to_le_bytes().to_vec();
+        reply.extend(&packet[2..4]);
+        reply.append(&mut body);
+        self.replies.push_back(reply);
+        Ok(())
+    }
+    fn recv(&mut self, _timeout: Duration) -> io::Result<Vec<u8>> {
+        self.replies.pop_front().ok_or(io::Error::new(io::ErrorKind::TimedOut, "No reply"))
+    }
+}
@@ -0,0 +1,342 @@
+//! Transport & client.
+//!
+//! [`Transport`] moves whole packets to and from brick. [`Client`] matches replies to commands by id
+//! and delivers unsolicited mailbox messages through separate channel.
+//!
+//! ### Transports
+//!  - [`StreamTransport`]: Any byte stream, e.g. Bluetooth serial port (`/dev/rfcomm0`, `COM3`)
+//!  - [`TcpTransport`]: WiFi, port 5555 with unlock handshake
+//!  - `HidTransport`: USB, with `usb` feature
+//!  - [`Recorder`]: Log traffic of another transport into file
+//!  - [`Replay`]: Return recorded replies, for tests without hardware
+//!  - [`MockBrick`]: Answer every command with empty reply, for tests without hardware
+//!
+//! [`Client::start_capture`] capture session, which can be exported as pcapng with [`Capture::to_pcapng`].
+//!
+//! # Example
+//! ```no_run
+//! use ev3_dc::{ Command, encode, Encoding::* };
+//! use ev3_dc::transport::{ Client, StreamTransport };
+//! use ev3_dc::mailbox::{ MailboxMessage, MailboxValue };
+//!
+//! let (mut client, mailbox) = Client::new(StreamTransport::open("/dev/rfcomm0").unwrap());
+//! let mut cmd = Command::new();
+//! cmd.bytecode = vec![0x82, 0x1B]; // opUI_Write LED
+//! cmd.bytecode.extend(encode(LC0(0x04)).unwrap());
+//! let rep = client.direct(&cmd).unwrap();
+//! client.send_mailbox(&MailboxMessage::new("cmd", MailboxValue::Numeric(1.0)).unwrap()).unwrap();
+//! client.poll(std::time::Duration::from_secs(1)).unwrap();
+//! for msg in mailbox.try_iter() {
+//!     println!("{}: {:?}", msg.name, msg.text());
+//! }
+//! ```
+
+use alloc::{ boxed::Box, format, string::{ String, ToString }, vec, vec::Vec };
+use std::fs::{ File, OpenOptions };
+use std::io::{ self, Read, Write };
+use std::net::{ TcpStream, ToSocketAddrs };
+use std::path::Path;
+use std::sync::mpsc::{ channel, Receiver, RecvTimeoutError, Sender };
+use std::thread;
+use std::time::{ Duration, Instant, SystemTime };
+use crate::{ Command, Error, ValError };
+use crate::error::{ ReplyError, SystemStatus };
+use crate::mailbox::{ is_mailbox, MailboxMessage };
+use crate::parser::Reply;
+use crate::system::{ begin_download, begin_upload, close_handle, continue_download, continue_list, continue_upload, list_files, SystemCommand, SystemReply, CHUNK_MAX };
+
+mod record;
+mod pcap;
+mod mock;
+#[cfg(feature = "usb")]
+mod usb;
+pub use record::*;
+pub use pcap::*;
+pub use mock::*;
+#[cfg(feature = "usb")]
+pub use usb::*;
+
+/// Packet transport
+pub trait Transport {
+    /// Send single packet
+    fn send(&mut self, packet: &[u8]) -> io::Result<()>;
+    /// Receive single packet, including 2 bytes length. \
+    /// Return [`io::ErrorKind::TimedOut`] or [`io::ErrorKind::WouldBlock`] if no packet arrived in time
+    fn recv(&mut self, timeout: Duration) -> io::Result<Vec<u8>>;
+}
+
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> { (**self).send(packet) }
+    fn recv(&mut self, timeout: Duration) -> io::Result<Vec<u8>> { (**self).recv(timeout) }
+}
+
+// Read length-prefixed packet
+fn read_packet<R: Read>(stream: &mut R) -> io::Result<Vec<u8>> {
+    let mut packet = vec![0_u8; 2];
+    stream.read_exact(&mut packet)?;
+    let len = u16::from_le_bytes([packet[0], packet[1]]) as usize;
+    packet.resize(2 + len, 0);
+    stream.read_exact(&mut packet[2..])?;
+    Ok(packet)
+}
+
+/// Transport over byte stream. With separate reading half, packets are read on background thread
+/// and timeout is enforced. Otherwise stream blocks until packet arrived
+/// # Example
+/// ```
+/// use std::io::Cursor;
+/// use std::time::Duration;
+/// use ev3_dc::transport::{ StreamTransport, Transport };
+///
+/// let mut transport = StreamTransport::with_reader(Cursor::new(vec![]), Cursor::new(vec![0x03, 0x00, 0x01, 0x00, 0x02]));
+/// assert_eq!(transport.recv(Duration::from_secs(1)).unwrap(), vec![0x03, 0x00, 0x01, 0x00, 0x02]);
+/// assert!(transport.recv(Duration::from_millis(10)).is_err()); // Stream ended
+/// ```
+pub struct StreamTransport<S: Read + Write> {
+    stream: S,
+    packets: Option<Receiver<io::Result<Vec<u8>>>>,
+}
+
+impl<S: Read + Write> StreamTransport<S> {
+    /// Use stream for both directions. Timeout is not enforced
+    pub fn new(stream: S) -> Self {
+        StreamTransport { stream, packets: None }
+    }
+    /// Write to stream, read packets from `reader` on background thread.
+    /// Thread ends when reader fails or transport is dropped and next packet arrives
+    pub fn with_reader<R: Read + Send + 'static>(stream: S, mut reader: R) -> Self {
+        let (tx, rx) = channel();
+        thread::spawn(move || loop {
+            let packet = read_packet(&mut reader);
+            let failed = packet.is_err();
+            if tx.send(packet).is_err() || failed { break }
+        });
+        StreamTransport { stream, packets: Some(rx) }
+    }
+}
+
+impl StreamTransport<File> {
+    /// Open serial device, e.g. Bluetooth `/dev/rfcomm0`. Timeout is enforced
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let reader = file.try_clone()?;
+        Ok(StreamTransport::with_reader(file, reader))
+    }
+}
+
+impl<S: Read + Write> Transport for StreamTransport<S> {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        self.stream.write_all(packet)?;
+        self.stream.flush()
+    }
+    fn recv(&mut self, timeout: Duration) -> io::Result<Vec<u8>> {
+        let Some(packets) = &self.packets else { return read_packet(&mut self.stream) };
+        match packets.recv_timeout(timeout) {
+            Ok(packet) => packet,
+            Err(RecvTimeoutError::Timeout) => Err(io::Error::new(io::ErrorKind::TimedOut, "No packet")),
+            Err(RecvTimeoutError::Disconnected) => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Stream closed"))
+        }
+    }
+}
+
+/// WiFi transport. Replies are read on background thread, like [`StreamTransport::with_reader`]
+pub struct TcpTransport {
+    stream: StreamTransport<TcpStream>,
+}
+
+impl TcpTransport {
+    /// Connect to brick at port 5555 and unlock with serial number (from UDP beacon on port 3015)
+    pub fn connect<A: ToSocketAddrs>(addr: A, serial: &str) -> io::Result<Self> {
+        let mut stream = TcpStream::connect(addr)?;
+        stream.write_all(format!("GET /target?sn={serial} VMTP1.0\r\nProtocol: EV3\r\n\r\n").as_bytes())?;
+        let mut buf = [0_u8; 16];
+        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+        let len = stream.read(&mut buf)?;
+        if !buf[..len].starts_with(b"Accept:EV340") {
+            return Err(io::Error::new(io::ErrorKind::ConnectionRefused, "EV3 refused connection"));
+        }
+        stream.set_read_timeout(None)?;
+        let reader = stream.try_clone()?;
+        Ok(TcpTransport { stream: StreamTransport::with_reader(stream, reader) })
+    }
+}
+
+impl Transport for TcpTransport {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        self.stream.send(packet)
+    }
+    fn recv(&mut self, timeout: Duration) -> io::Result<Vec<u8>> {
+        self.stream.recv(timeout)
+    }
+}
+
+/// Brick client. Match replies to commands and route mailbox messages to channel
+pub struct Client<T: Transport> {
+    transport: T,
+    mailbox: Sender<MailboxMessage>,
+    capture: Option<(Instant, Capture)>,
+    /// Reply timeout
+    pub timeout: Duration,
+}
+
+impl<T: Transport> Client<T> {
+    /// Create client and receiver of mailbox messages from brick
+    pub fn new(transport: T) -> (Self, Receiver<MailboxMessage>) {
+        let (tx, rx) = channel();
+        (Client { transport, mailbox: tx, capture: None, timeout: Duration::from_secs(5) }, rx)
+    }
+    /// Get underlying transport
+    pub fn transport(&mut self) -> &mut T {
+        &mut self.transport
+    }
+    /// Start capturing every sent & received packet. Restart if already capturing
+    /// # Example
+    /// Export session as pcapng
+    /// ```no_run
+    /// use ev3_dc::transport::{ Client, StreamTransport };
+    ///
+    /// let (mut client, _) = Client::new(StreamTransport::open("/dev/rfcomm0").unwrap());
+    /// client.start_capture();
+    /// // Send commands
+    /// let capture = client.take_capture().unwrap();
+    /// std::fs::write("session.pcapng", capture.to_pcapng()).unwrap();
+    /// ```
+    pub fn start_capture(&mut self) {
+        self.capture = Some((Instant::now(), Capture { start: SystemTime::now(), events: vec![] }));
+    }
+    /// Stop capturing and return captured packets
+    pub fn take_capture(&mut self) -> Option<Capture> {
+        self.capture.take().map(|(_, capture)| capture)
+    }
+    fn log(&mut self, direction: Direction, packet: &[u8]) {
+        if let Some((start, capture)) = &mut self.capture {
+            capture.events.push(Event { time: start.elapsed(), direction, packet: packet.to_vec() });
+        }
+    }
+    // Send and capture packet
+    fn transmit(&mut self, packet: &[u8]) -> io::Result<()> {
+        self.log(Direction::Sent, packet);
+        self.transport.send(packet)
+    }
+    /// Send raw packet without waiting for reply
+    pub fn send(&mut self, packet: &[u8]) -> Result<(), Error> {
+        Ok(self.transmit(packet)?)
+    }
+    // Receive packet that isn't mailbox message
+    fn recv(&mut self, deadline: Instant) -> Result<Vec<u8>, Error> {
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() { return Err(Error::Timeout(self.timeout)) }
+            let packet = match self.transport.recv(left) {
+                Ok(packet) => packet,
+                Err(err) if matches!(err.kind(), io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock) => {
+                    return Err(Error::Timeout(self.timeout))
+                }
+                Err(err) => return Err(err.into())
+            };
+            self.log(Direction::Received, &packet);
+            if is_mailbox(&packet) {
+                if let Ok(msg) = MailboxMessage::parse(&packet) { let _ = self.mailbox.send(msg); }
+                continue;
+            }
+            return Ok(packet);
+        }
+    }
+    // Receive reply with id, dropping stale replies
+    fn reply(&mut self, id: u16) -> Result<Vec<u8>, Error> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let packet = self.recv(deadline)?;
+            if packet.len() < 5 { return Err(ReplyError::Truncated(packet.len(), 5).into()) }
+            if u16::from_le_bytes([packet[2], packet[3]]) == id { return Ok(packet) }
+        }
+    }
+    /// Send direct command and wait for reply. Command must request reply
+    pub fn direct(&mut self, cmd: &Command) -> Result<Reply, Error> {
+        self.transmit(&cmd.gen_bytes())?;
+        let packet = self.reply(cmd.id)?;
+        match packet[4] {
+            0x02 => Ok(Reply::parse(&packet)),
+            0x04 => Err(ReplyError::Direct(cmd.id).into()),
+            other => Err(ReplyError::UnknownType(other).into())
+        }
+    }
+    /// Send system command and wait for reply. Non-success status is returned as [`Error::System`]
+    pub fn system(&mut self, cmd: &SystemCommand) -> Result<SystemReply, Error> {
+        self.transmit(&cmd.gen_bytes())?;
+        let rep = SystemReply::parse(&self.reply(cmd.id)?)?;
+        rep.check()?;
+        Ok(rep)
+    }
+    /// Download (host to brick) file in chunks
+    pub fn download(&mut self, path: &str, data: &[u8]) -> Result<(), Error> {
+        let size = u32::try_from(data.len()).map_err(|_| ValError::PosOverflow(u32::MAX, u32::MAX))?;
+        let rep = self.system(&begin_download(path, size)?)?;
+        let handle = *rep.payload().first().ok_or(ReplyError::Truncated(rep.payload().len(), 1))?;
+        for chunk in data.chunks(CHUNK_MAX) {
+            self.system(&continue_download(handle, chunk))?;
+        }
+        Ok(())
+    }
+    /// Upload (brick to host) whole file
+    /// # Example
+    /// Upload from scripted transport
+    /// ```
+    /// use std::io;
+    /// use std::time::Duration;
+    /// use ev3_dc::transport::{ Client, Transport };
+    ///
+    /// struct Script(Vec<Vec<u8>>);
+    /// impl Transport for Script {
+    ///     fn send(&mut self, _: &[u8]) -> io::Result<()> { Ok(()) }
+    ///     fn recv(&mut self, _: Duration) -> io::Result<Vec<u8>> { Ok(self.0.remove(0)) }
+    /// }
+    ///
+    /// let (mut client, _) = Client::new(Script(vec![
+    ///     vec![0x0C, 0x00, 0xAA, 0x00, 0x03, 0x94, 0x00, 0x04, 0x00, 0x00, 0x00, 0x01, b'a', b'b'], // size 4, handle 1
+    ///     vec![0x08, 0x00, 0xAA, 0x00, 0x03, 0x95, 0x08, 0x01, b'c', b'd'], // END_OF_FILE
+    /// ]));
+    /// assert_eq!(client.upload("../prjs/log.rdf").unwrap(), b"abcd");
+    /// ```
+    pub fn upload(&mut self, path: &str) -> Result<Vec<u8>, Error> {
+        self.transfer(&begin_upload(path, CHUNK_MAX as u16)?, continue_upload)
+    }
+    /// List folder. Folders end with `/`, files are `<MD5> <size in hex> <name>`
+    pub fn list(&mut self, path: &str) -> Result<String, Error> {
+        let data = self.transfer(&list_files(path, CHUNK_MAX as u16)?, continue_list)?;
+        Ok(String::from_utf8_lossy(&data).to_string())
+    }
+    // Read chunked reply of upload or listing: size (4 bytes), handle and data
+    fn transfer(&mut self, begin: &SystemCommand, next: fn(u8, u16) -> SystemCommand) -> Result<Vec<u8>, Error> {
+        let rep = self.system(begin)?;
+        let payload = rep.payload();
+        if payload.len() < 5 { return Err(ReplyError::Truncated(payload.len(), 5).into()) }
+        let size = u32::from_le_bytes([payload[0], payload[1], payload[2], payload[3]]) as usize;
+        let handle = payload[4];
+        let mut data = payload[5..].to_vec();
+        let mut end = rep.status() == SystemStatus::EndOfFile;
+        while data.len() < size && !end {
+            let rep = self.system(&next(handle, CHUNK_MAX as u16))?;
+            end = rep.status() == SystemStatus::EndOfFile;
+            data.extend(rep.payload().get(1..).unwrap_or(&[]));
+        }
+        if !end { self.system(&close_handle(handle))?; }
+        data.truncate(size);
+        Ok(data)
+    }
+    /// Send mailbox message to brick
+    pub fn send_mailbox(&mut self, msg: &MailboxMessage) -> Result<(), Error> {
+        Ok(self.transmit(&msg.to_command()?.gen_bytes())?)
+    }
+    /// Receive packets for `duration`, delivering mailbox messages. Other packets are dropped
+    pub fn poll(&mut self, duration: Duration) -> Result<(), Error> {
+        let deadline = Instant::now() + duration;
+        loop {
+            match self.recv(deadline) {
+                Ok(_) => {}
+                Err(Error::Timeout(_)) => return Ok(()),
+                Err(err) => return Err(err)
+            }
+        }
+    }
+}
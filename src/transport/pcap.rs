@@ -0,0 +1,141 @@
+//! pcapng export
+
+use alloc::{ format, string::{ String, ToString }, vec, vec::Vec };
+use std::io::{ self, Write };
+use std::time::UNIX_EPOCH;
+use crate::opcode::instruction;
+use super::{ Capture, Direction };
+
+/// Link type of exported packets (LINKTYPE_USER0)
+pub const LINKTYPE_EV3: u16 = 147;
+
+// pcapng option
+fn option(block: &mut Vec<u8>, code: u16, value: &[u8]) {
+    block.extend(code.to_le_bytes());
+    block.extend((value.len() as u16).to_le_bytes());
+    block.extend(value);
+    block.resize(block.len().next_multiple_of(4), 0);
+}
+
+// Wrap body into block with type & total length
+fn block(kind: u32, mut body: Vec<u8>) -> Vec<u8> {
+    body.resize(body.len().next_multiple_of(4), 0);
+    let len = (12 + body.len() as u32).to_le_bytes();
+    let mut bytes = kind.to_le_bytes().to_vec();
+    bytes.extend(len);
+    bytes.extend(body);
+    bytes.extend(len);
+    bytes
+}
+
+// Disassembly listing of bytecode, unknown bytes are shown as hex
+fn listing(bytecode: &[u8]) -> String {
+    let mut lines = vec![];
+    let mut pos = 0;
+    while pos < bytecode.len() {
+        match instruction(bytecode, pos) {
+            Ok(ins) => {
+                lines.push(ins.to_string());
+                pos += ins.length;
+            }
+            Err(_) => {
+                lines.push(format!("{pos:04}  ?? {:02X?}", &bytecode[pos..]));
+                break;
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+/// Describe packet: type, id and disassembly of direct command
+/// # Example
+/// ```
+/// use ev3_dc::transport::describe;
+///
+/// let text = describe(&[0x09, 0x00, 0x2A, 0x00, 0x80, 0x00, 0x00, 0x82, 0x1B, 0x04]);
+/// assert_eq!(text, "DIRECT_COMMAND_NO_REPLY id=42 global=0 local=0\n0000  UI_WRITE.LED LC0(4)");
+/// assert_eq!(describe(&[0x03, 0x00, 0x2A, 0x00, 0x02]), "DIRECT_REPLY id=42 memory=0");
+/// ```
+pub fn describe(packet: &[u8]) -> String {
+    if packet.len() < 5 { return format!("TRUNCATED {packet:02X?}") }
+    let id = u16::from_le_bytes([packet[2], packet[3]]);
+    let body = &packet[5..];
+    match packet[4] {
+        kind @ (0x00 | 0x80) => {
+            let name = if kind == 0x00 { "DIRECT_COMMAND_REPLY" } else { "DIRECT_COMMAND_NO_REPLY" };
+            if body.len() < 2 { return format!("{name} id={id}") }
+            let alloc = u16::from_le_bytes([body[0], body[1]]);
+            format!("{name} id={id} global={} local={}\n{}", alloc & 0x3FF, alloc >> 10, listing(&body[2..]))
+        }
+        kind @ (0x01 | 0x81) => {
+            let name = if kind == 0x01 { "SYSTEM_COMMAND_REPLY" } else { "SYSTEM_COMMAND_NO_REPLY" };
+            format!("{name} id={id} command={:#04X}", body.first().copied().unwrap_or(0))
+        }
+        0x02 => format!("DIRECT_REPLY id={id} memory={}", body.len()),
+        0x04 => format!("DIRECT_REPLY_ERROR id={id}"),
+        kind @ (0x03 | 0x05) => {
+            let name = if kind == 0x03 { "SYSTEM_REPLY" } else { "SYSTEM_REPLY_ERROR" };
+            format!("{name} id={id} command={:#04X} status={:#04X}", body.first().copied().unwrap_or(0), body.get(1).copied().unwrap_or(0))
+        }
+        other => format!("UNKNOWN({other:#04X}) id={id}")
+    }
+}
+
+impl Capture {
+    /// Write capture as pcapng. Each packet has direction flag & [`describe`] comment
+    /// # Example
+    /// ```
+    /// use std::time::{ Duration, UNIX_EPOCH };
+    /// use ev3_dc::transport::{ Capture, Direction, Event };
+    ///
+    /// let capture = Capture { start: UNIX_EPOCH, events: vec![
+    ///     Event { time: Duration::ZERO, direction: Direction::Sent, packet: vec![0x05, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00] },
+    ///     Event { time: Duration::from_millis(5), direction: Direction::Received, packet: vec![0x03, 0x00, 0x01, 0x00, 0x02] },
+    /// ]};
+    /// let bytes = capture.to_pcapng();
+    /// assert_eq!(&bytes[..4], &[0x0A, 0x0D, 0x0D, 0x0A]); // Section header
+    /// ```
+    pub fn write_pcapng<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut shb = 0x1A2B3C4D_u32.to_le_bytes().to_vec(); // Byte-order magic
+        shb.extend(1_u16.to_le_bytes());
+        shb.extend(0_u16.to_le_bytes());
+        shb.extend((-1_i64).to_le_bytes()); // Unknown section length
+        option(&mut shb, 4, b"ev3-dc"); // shb_userappl
+        option(&mut shb, 0, &[]);
+        writer.write_all(&block(0x0A0D0D0A, shb))?;
+
+        let mut idb = LINKTYPE_EV3.to_le_bytes().to_vec();
+        idb.extend(0_u16.to_le_bytes());
+        idb.extend(0_u32.to_le_bytes()); // No snap length
+        option(&mut idb, 2, b"ev3"); // if_name
+        option(&mut idb, 0, &[]);
+        writer.write_all(&block(1, idb))?;
+
+        let start = self.start.duration_since(UNIX_EPOCH).unwrap_or_default();
+        for event in &self.events {
+            let micros = (start + event.time).as_micros() as u64;
+            let mut epb = 0_u32.to_le_bytes().to_vec(); // Interface id
+            epb.extend(((micros >> 32) as u32).to_le_bytes());
+            epb.extend((micros as u32).to_le_bytes());
+            epb.extend((event.packet.len() as u32).to_le_bytes());
+            epb.extend((event.packet.len() as u32).to_le_bytes());
+            epb.extend(&event.packet);
+            epb.resize(epb.len().next_multiple_of(4), 0);
+            let flags: u32 = match event.direction {
+                Direction::Received => 0b01,
+                Direction::Sent => 0b10
+            };
+            option(&mut epb, 2, &flags.to_le_bytes()); // epb_flags
+            option(&mut epb, 1, describe(&event.packet).as_bytes()); // opt_comment
+            option(&mut epb, 0, &[]);
+            writer.write_all(&block(6, epb))?;
+        }
+        Ok(())
+    }
+    /// Get capture as pcapng bytes
+    pub fn to_pcapng(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        let _ = self.write_pcapng(&mut bytes);
+        bytes
+    }
+}
@@ -0,0 +1,205 @@
+//! Traffic recorder & replay
+
+use alloc::{ format, string::String, vec, vec::Vec };
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{ self, BufRead, BufReader, Write };
+use std::path::Path;
+use std::time::{ Duration, Instant, SystemTime, UNIX_EPOCH };
+use super::Transport;
+
+/// Packet direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    /// Host to brick
+    Sent,
+    /// Brick to host
+    Received,
+}
+
+/// Recorded packet
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Event {
+    /// Time since recording started
+    pub time: Duration,
+    /// Packet direction
+    pub direction: Direction,
+    /// Whole packet, including 2 bytes length
+    pub packet: Vec<u8>,
+}
+
+impl Event {
+    /// Format event as log line: `<µs> <S|R> <hex>`
+    pub fn to_line(&self) -> String {
+        let dir = match self.direction {
+            Direction::Sent => 'S',
+            Direction::Received => 'R'
+        };
+        let hex: String = self.packet.iter().map(|b| format!("{b:02X}")).collect();
+        format!("{} {dir} {hex}", self.time.as_micros())
+    }
+    /// Parse log line
+    pub fn from_line(line: &str) -> Option<Self> {
+        let mut fields = line.split_whitespace();
+        let time = Duration::from_micros(fields.next()?.parse().ok()?);
+        let direction = match fields.next()? {
+            "S" => Direction::Sent,
+            "R" => Direction::Received,
+            _ => return None
+        };
+        let hex = fields.next().unwrap_or("");
+        if !hex.len().is_multiple_of(2) || fields.next().is_some() { return None }
+        let packet = (0..hex.len()).step_by(2)
+            .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+        Some(Event { time, direction, packet })
+    }
+}
+
+/// Traffic log. Text with header line `# ev3-dc capture <start µs since UNIX epoch>`, then one [`Event`] per line
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Capture {
+    /// Recording start
+    pub start: SystemTime,
+    /// Events in order
+    pub events: Vec<Event>,
+}
+
+const HEADER: &str = "# ev3-dc capture";
+
+impl Capture {
+    /// Parse capture log
+    pub fn parse<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut capture = Capture { start: UNIX_EPOCH, events: vec![] };
+        for (num, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if let Some(start) = line.strip_prefix(HEADER) {
+                let micros = start.trim().parse().unwrap_or(0);
+                capture.start = UNIX_EPOCH + Duration::from_micros(micros);
+                continue;
+            }
+            if line.is_empty() || line.starts_with('#') { continue }
+            let event = Event::from_line(line)
+                .ok_or(io::Error::new(io::ErrorKind::InvalidData, format!("Invalid event at line {}", num + 1)))?;
+            capture.events.push(event);
+        }
+        Ok(capture)
+    }
+    /// Read capture log file
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Capture::parse(BufReader::new(File::open(path)?))
+    }
+}
+
+/// Transport that logs every packet to writer, in [`Capture`] format
+/// # Example
+/// Record traffic, then replay it
+/// ```
+/// use std::io;
+/// use std::time::Duration;
+/// use ev3_dc::transport::{ Capture, Recorder, Replay, Transport };
+///
+/// struct Echo(Vec<u8>);
+/// impl Transport for Echo {
+///     fn send(&mut self, packet: &[u8]) -> io::Result<()> { self.0 = packet.to_vec(); Ok(()) }
+///     fn recv(&mut self, _: Duration) -> io::Result<Vec<u8>> { Ok(vec![0x03, 0x00, self.0[2], self.0[3], 0x02]) }
+/// }
+///
+/// let mut log = vec![];
+/// let mut rec = Recorder::new(Echo(vec![]), &mut log).unwrap();
+/// rec.send(&[0x05, 0x00, 0x01, 0x00, 0x80, 0x00, 0x00]).unwrap();
+/// let reply = rec.recv(Duration::from_secs(1)).unwrap();
+/// drop(rec);
+///
+/// let mut replay = Replay::new(Capture::parse(&log[..]).unwrap());
+/// assert!(replay.send(&[0x05, 0x00, 0x02, 0x00, 0x80, 0x00, 0x00]).is_err()); // Diverged
+/// replay.send(&[0x05, 0x00, 0x01, 0x00, 0x80, 0x00, 0x00]).unwrap();
+/// assert_eq!(replay.recv(Duration::from_secs(1)).unwrap(), reply);
+/// assert_eq!(replay.remaining(), 0);
+/// ```
+pub struct Recorder<T: Transport, W: Write> {
+    inner: T,
+    writer: W,
+    start: Instant,
+}
+
+impl<T: Transport, W: Write> Recorder<T, W> {
+    /// Wrap transport and write capture header
+    pub fn new(inner: T, mut writer: W) -> io::Result<Self> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        writeln!(writer, "{HEADER} {}", now.as_micros())?;
+        Ok(Recorder { inner, writer, start: Instant::now() })
+    }
+    /// Get wrapped transport
+    pub fn inner(&mut self) -> &mut T {
+        &mut self.inner
+    }
+    fn log(&mut self, direction: Direction, packet: &[u8]) -> io::Result<()> {
+        let event = Event { time: self.start.elapsed(), direction, packet: packet.to_vec() };
+        writeln!(self.writer, "{}", event.to_line())?;
+        self.writer.flush()
+    }
+}
+
+impl<T: Transport> Recorder<T, File> {
+    /// Wrap transport and record into new file
+    pub fn create<P: AsRef<Path>>(inner: T, path: P) -> io::Result<Self> {
+        Recorder::new(inner, File::create(path)?)
+    }
+}
+
+impl<T: Transport, W: Write> Transport for Recorder<T, W> {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        self.log(Direction::Sent, packet)?;
+        self.inner.send(packet)
+    }
+    fn recv(&mut self, timeout: Duration) -> io::Result<Vec<u8>> {
+        let packet = self.inner.recv(timeout)?;
+        self.log(Direction::Received, &packet)?;
+        Ok(packet)
+    }
+}
+
+/// Transport returning recorded replies in order. Sent packets must match recording
+pub struct Replay {
+    events: VecDeque<Event>,
+}
+
+impl Replay {
+    /// Replay capture
+    pub fn new(capture: Capture) -> Self {
+        Replay { events: capture.events.into() }
+    }
+    /// Replay capture log file
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Replay::new(Capture::open(path)?))
+    }
+    /// Get events not replayed yet
+    pub fn remaining(&self) -> usize {
+        self.events.len()
+    }
+}
+
+impl Transport for Replay {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        match self.events.front() {
+            Some(event) if event.direction == Direction::Sent && event.packet == packet => {
+                self.events.pop_front();
+                Ok(())
+            }
+            Some(event) => Err(io::Error::new(io::ErrorKind::InvalidData,
+                format!("Diverged at {:?}: expect {:?} {:02X?} got {packet:02X?}", event.time, event.direction, event.packet))),
+            None => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Recording ended"))
+        }
+    }
+    fn recv(&mut self, _timeout: Duration) -> io::Result<Vec<u8>> {
+        match self.events.front() {
+            Some(event) if event.direction == Direction::Received => Ok(self.events.pop_front().unwrap().packet),
+            _ => Err(io::Error::new(io::ErrorKind::TimedOut, "No recorded reply"))
+        }
+    }
+}
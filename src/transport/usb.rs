@@ -0,0 +1,51 @@
+//! USB HID transport
+
+use alloc::{ vec, vec::Vec };
+use std::io;
+use std::time::Duration;
+use hidapi::{ HidApi, HidDevice };
+use crate::{ PID, VID };
+use super::Transport;
+
+// Report size of EV3 HID interface
+const REPORT_SIZE: usize = 1024;
+
+/// USB transport. Require `usb` feature
+pub struct HidTransport {
+    device: HidDevice,
+}
+
+impl HidTransport {
+    /// Open first EV3, or EV3 with serial number
+    pub fn open(serial: Option<&str>) -> io::Result<Self> {
+        let api = HidApi::new().map_err(io::Error::other)?;
+        let device = match serial {
+            Some(serial) => api.open_serial(VID, PID, serial),
+            None => api.open(VID, PID)
+        }.map_err(|err| io::Error::new(io::ErrorKind::NotFound, err))?;
+        Ok(HidTransport { device })
+    }
+    /// Wrap opened device
+    pub fn new(device: HidDevice) -> Self {
+        HidTransport { device }
+    }
+}
+
+impl Transport for HidTransport {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        let mut report = vec![0x00]; // Report id
+        report.extend(packet);
+        self.device.write(&report).map_err(io::Error::other)?;
+        Ok(())
+    }
+    fn recv(&mut self, timeout: Duration) -> io::Result<Vec<u8>> {
+        let mut buf = vec![0_u8; REPORT_SIZE];
+        let millis = timeout.as_millis().clamp(1, i32::MAX as u128) as i32;
+        let len = self.device.read_timeout(&mut buf, millis).map_err(io::Error::other)?;
+        if len == 0 { return Err(io::Error::new(io::ErrorKind::TimedOut, "No report")) }
+        if len < 2 { return Err(io::Error::new(io::ErrorKind::InvalidData, "Short report")) }
+        let size = 2 + u16::from_le_bytes([buf[0], buf[1]]) as usize;
+        buf.truncate(size.min(len));
+        Ok(buf)
+    }
+}
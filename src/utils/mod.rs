@@ -3,6 +3,7 @@
 //! ### Terminology
 //!  - **Layer**: Index of daisy-chained EV3. i.e a single EV3 brick is a master which has layer of 0
 
+use alloc::{ collections::BTreeMap, format, string::{ String, ToString }, vec, vec::Vec };
 use super::{ Encoding::*, encode, ValError };
 
 #[derive(Default)]
@@ -10,18 +11,48 @@ use super::{ Encoding::*, encode, ValError };
 /// # Example
 /// Create u8 vector with chainable method to modify value
 /// ```
+/// use ev3_dc::utils::ChainByte;
+///
 /// // Compared to standard rust vector operation
 /// let mut byte = ChainByte::new(); // let mut byte: Vec<u8> = vec![];
 /// byte.push(0x81) // byte.push(0x81);
-///     .add(vec![0x1B, 0x00]) // byte.extend(vec![0x1B, 0x00]);
+///     .add(vec![0x1B, 0x00]); // byte.extend(vec![0x1B, 0x00]);
 /// println!("Vector: {:02X?}", byte.bytes); // println!("Vector: {:02X?}", byte);
 /// ```
+///
+/// Loop until touch sensor at port 1 is pressed, with label & jump
+/// ```
+/// use ev3_dc::{ encode, Encoding::* };
+/// use ev3_dc::utils::ChainByte;
+/// use ev3_dc::opcode::disassemble;
+///
+/// let mut byte = ChainByte::new();
+/// byte.label("wait")
+///     .push(0x9A) // opInput_Read
+///     .add(encode(LC0(0)).unwrap()) // Layer
+///     .add(encode(LC0(0)).unwrap()) // Port
+///     .add(encode(LC0(0)).unwrap()) // Type (Don't change)
+///     .add(encode(LC0(0)).unwrap()) // Mode
+///     .add(encode(LV0(0)).unwrap()) // Percentage
+///     .jump(0x6C, encode(LV0(0)).unwrap().into_iter().chain(encode(LC0(0)).unwrap()).collect(), "wait") // opJr_Eq8
+///     .jump(0x40, vec![], "end") // opJr
+///     .push(0x01) // opNop, skipped
+///     .label("end");
+/// byte.resolve().unwrap();
+/// let code = disassemble(&byte.bytes).unwrap();
+/// assert_eq!(code[1].target, Some(0));
+/// assert_eq!(code[2].target, Some(byte.bytes.len()));
+/// ```
 pub struct ChainByte {
     /// Result vector
-    pub bytes: Vec<u8>
+    pub bytes: Vec<u8>,
+    labels: BTreeMap<String, usize>,
+    // (parameter offset, label)
+    fixups: Vec<(usize, String)>,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Single line struct for RLE
 pub struct Span {
     pub x1: u8,
@@ -29,12 +60,13 @@ pub struct Span {
     pub y: u8
 }
 
-const LEN_MAX: usize = 1000; // LIMIT: Practical limit is 1000 for some reason.
+/// Maximum bytecode length of single command
+pub const LEN_MAX: usize = 1000; // LIMIT: Practical limit is 1000 for some reason.
 
 // maybe use velcro crate instead
 impl ChainByte {
     pub fn new() -> Self {
-        ChainByte { bytes: vec![] }
+        ChainByte::default()
     }
     /// Same as [`Vec::push`], but chainable
     pub fn push(&mut self, byte: u8) -> &mut Self {
@@ -46,35 +78,73 @@ impl ChainByte {
         self.bytes.extend(bytes);
         self
     }
+    /// Mark current position as jump target
+    pub fn label(&mut self, name: &str) -> &mut Self {
+        self.labels.insert(name.to_string(), self.bytes.len());
+        self
+    }
+    /// Push jumping OpCode with encoded parameters before offset, jumping to `label`. \
+    /// Offset is always encoded as [`crate::Encoding::LC2`] and patched by [`ChainByte::resolve`]
+    pub fn jump(&mut self, opcode: u8, params: Vec<u8>, label: &str) -> &mut Self {
+        self.bytes.push(opcode);
+        self.bytes.extend(params);
+        self.fixups.push((self.bytes.len(), label.to_string()));
+        self.bytes.extend([0x82, 0x00, 0x00]);
+        self
+    }
+    /// Patch jump offsets to their labels
+    pub fn resolve(&mut self) -> Result<&mut Self, ValError> {
+        for (pos, label) in &self.fixups {
+            let target = *self.labels.get(label).ok_or(ValError::Undefined(label.clone()))?;
+            let offset = target as i64 - (pos + 3) as i64;
+            let offset = i16::try_from(offset).map_err(|_| ValError::InvalidRange(offset as i32, i16::MIN as i32, i16::MAX as i32))?;
+            self.bytes[(pos + 1)..(pos + 3)].copy_from_slice(&offset.to_le_bytes());
+        }
+        Ok(self)
+    }
 }
 
 /// Encode local constant based on integer value
+/// # Example
+/// ```
+/// use ev3_dc::utils::auto_const;
+///
+/// assert_eq!(auto_const(-32).unwrap(), vec![0x20]);
+/// assert_eq!(auto_const(-33).unwrap(), vec![0x81, 0xDF]);
+/// assert_eq!(auto_const(128).unwrap(), vec![0x82, 0x80, 0x00]);
+/// ```
 pub fn auto_const(val: i32) -> Result<Vec<u8>, ValError> {
-    if val == -32 { return encode(LC0(val as i8)) }
-    match val.abs() {
-        0..32 => encode(LC0(val as i8)),
-        32..128 => encode(LC1(val as i8)),
-        128..32768 => encode(LC2(val as i16)),
+    match val {
+        -32..32 => encode(LC0(val as i8)),
+        -128..128 => encode(LC1(val as i8)),
+        -32768..32768 => encode(LC2(val as i16)),
         _ => encode(LC4(val))
     }
 }
 
 /// Merge vector of small bytecode to vector of larger bytecode vector.
-/// Max length is set to 1000
+/// Max length is [`LEN_MAX`]. Bytecode longer than that is kept in its own vector. \
+/// Use [`crate::planner`] to also split variables
+/// # Example
+/// ```
+/// use ev3_dc::utils::package_bytes;
+///
+/// assert!(package_bytes(&[]).is_empty());
+/// let packets = package_bytes(&[vec![0x01; 600], vec![0x01; 1200], vec![0x01; 300], vec![0x01; 300]]);
+/// assert_eq!(packets.iter().map(|p| p.len()).collect::<Vec<_>>(), vec![600, 1200, 600]);
+/// ```
 pub fn package_bytes(bytecodes: &[Vec<u8>]) -> Vec<Vec<u8>> {
     let mut packets: Vec<Vec<u8>> = vec![];
     let mut buffer: Vec<u8> = Vec::with_capacity(LEN_MAX);
-    let mut size: usize = 0;
     for bytes in bytecodes {
-       if size + bytes.len() > LEN_MAX {
-            packets.push(buffer.clone());
-            buffer.clear();
-            size = 0;
+        if !buffer.is_empty() && buffer.len() + bytes.len() > LEN_MAX {
+            packets.push(core::mem::take(&mut buffer));
         }
         buffer.extend(bytes);
-        size += bytes.len();
     }
-    packets.push(buffer);
+    if !buffer.is_empty() {
+        packets.push(buffer);
+    }
     packets
 }
 
@@ -112,10 +182,12 @@ pub fn run_length(image: &[u8]) -> Result<Vec<Span>, ValError> {
 /// # Example
 /// create RLE line vector from 178x128 binary vector and create line bytecode
 /// ```
+/// use ev3_dc::utils::{ run_length, printer };
+///
 /// let img: [u8; 22784] = [1; 22784]; // add stuff here
 /// let lines = run_length(&img).unwrap();
 /// let code = printer(&lines);
-/// println("Bytecode: {:02X?}", code);
+/// println!("Bytecode: {:02X?}", code);
 /// ```
 pub fn printer(lines: &[Span]) -> Vec<Vec<u8>> {
     let mut packets: Vec<Vec<u8>> = vec![];
@@ -124,7 +196,7 @@ pub fn printer(lines: &[Span]) -> Vec<Vec<u8>> {
         if line.x1 == line.x2 {
             bytecode.add(vec![0x84, 0x02, 0x01])
                 .add(encode(LC2(line.x1 as i16)).unwrap())
-                .add(encode(LC2(line.x2 as i16)).unwrap());
+                .add(encode(LC2(line.y as i16)).unwrap());
         }else {
             bytecode.add(vec![0x84, 0x03, 0x01])
                 .add(encode(LC2(line.x1 as i16)).unwrap())
@@ -137,8 +209,14 @@ pub fn printer(lines: &[Span]) -> Vec<Vec<u8>> {
     packets
 }
 
-/// Return name of device id
-// will probably get replaced soon
+/// Return name of device id. Other ids (e.g. NXT or third-party devices) are named `Unknown(<id>)`
+/// # Example
+/// ```
+/// use ev3_dc::utils::device_id;
+///
+/// assert_eq!(device_id(7), "Large-Motor");
+/// assert_eq!(device_id(0), "Unknown(0)");
+/// ```
 pub fn device_id(byte: u8) -> String {
     String::from(match byte {
         7 => "Large-Motor",
@@ -151,7 +229,7 @@ pub fn device_id(byte: u8) -> String {
         33 => "IR-Sensor",
         126 => "None",
         127 => "Port-Error",
-        _ => todo!("ID: {} Unimplemented!", byte) // for now, only support EV3 devices
+        _ => return format!("Unknown({byte})")
     })
 }
 
@@ -159,9 +237,12 @@ pub fn device_id(byte: u8) -> String {
 /// # Example
 /// read ports of the master / first EV3 brick
 /// ```
-/// let buf: [u8; 32] = [0x7E, 0X7E, 0x08]; // Reply memory from opInput_Device_List OpCode
-/// let res: [u8; 8] = port_read(&buf, 0);
-/// println!("Input device ids: {:?}, Output device ids: {:?}", res[0..4], res[4..8]);
+/// use ev3_dc::utils::port_read;
+///
+/// let buf: [u8; 32] = [0x7E, 0x7E, 0x08, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E,
+///     0x07, 0x07, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E, 0x7E]; // Reply memory from opInput_Device_List OpCode
+/// let res: [u8; 8] = port_read(&buf, 0).unwrap();
+/// println!("Input device ids: {:?}, Output device ids: {:?}", &res[0..4], &res[4..8]);
 /// ```
 pub fn port_read(port: &[u8], layer: u8) -> Result<[u8; 8], ValError> {
     let mut ports = [0_u8; 8];
@@ -176,3 +257,21 @@ pub fn port_read(port: &[u8], layer: u8) -> Result<[u8; 8], ValError> {
 pub fn read_string(bytes: &[u8]) -> Option<&str> {
     str::from_utf8(bytes).unwrap().split_terminator("\0").next()
 }
+
+/// Quote & escape string as JSON string
+pub fn json_string(text: &str) -> String {
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            '\n' => out += "\\n",
+            '\r' => out += "\\r",
+            '\t' => out += "\\t",
+            c if (c as u32) < 0x20 => out += &format!("\\u{:04x}", c as u32),
+            c => out.push(c)
+        }
+    }
+    out.push('"');
+    out
+}